@@ -0,0 +1,138 @@
+/*
+ * Benchmarks of the rules on a handful of positions, run with `cargo bench`.
+ * The numbers of the last run on the reference machine are in BENCHMARKS.md,
+ * update them when a change makes any of these faster or slower.
+ */
+#[macro_use]
+extern crate criterion;
+extern crate ChessAPI;
+
+use criterion::{black_box, BenchmarkId, Criterion};
+use ChessAPI::board::{Board, Move};
+use ChessAPI::perft::{perft, PerftOptions};
+use ChessAPI::square::Square;
+
+// A name for the report and a FEN
+const POSITIONS: [(&str, &str); 4] = [
+    (
+        "opening",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    ),
+    (
+        "middlegame",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    ),
+    ("endgame", "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"),
+    ("promotion", "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1"),
+];
+
+// Checkmate has to look for a legal move to be sure, which is the slow case
+const CHECKMATE: &str = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3";
+
+fn positions() -> Vec<(&'static str, Board)> {
+    POSITIONS
+        .iter()
+        .map(|&(name, fen)| (name, Board::from_fen(fen).unwrap()))
+        .collect()
+}
+
+// Every move of a piece of the player to move to any other square, legal or not
+fn candidate_moves(board: &Board) -> Vec<Move> {
+    let mut moves = Vec::new();
+    for from in Square::ALL {
+        if board
+            .piece_at(from)
+            .is_none_or(|piece| piece.color != board.whose_turn())
+        {
+            continue;
+        }
+        for to in Square::ALL {
+            if to != from {
+                moves.push(Move::new(from, to, None));
+            }
+        }
+    }
+    moves
+}
+
+fn generate_legal_moves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate_legal_moves");
+    for (name, board) in positions() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &board, |b, board| {
+            b.iter(|| black_box(board).generate_legal_moves())
+        });
+    }
+    group.finish();
+}
+
+fn is_legal(c: &mut Criterion) {
+    let mut group = c.benchmark_group("is_legal");
+    for (name, board) in positions() {
+        let moves = candidate_moves(&board);
+        group.bench_with_input(BenchmarkId::from_parameter(name), &board, |b, board| {
+            b.iter(|| {
+                moves
+                    .iter()
+                    .filter(|mv| black_box(board).is_legal(mv))
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn make_move(c: &mut Criterion) {
+    let mut group = c.benchmark_group("make_move");
+    for (name, board) in positions() {
+        let moves = board.generate_legal_moves();
+        group.bench_with_input(BenchmarkId::from_parameter(name), &board, |b, board| {
+            b.iter(|| {
+                for mv in moves.iter() {
+                    let mut after = *black_box(board);
+                    after.make_move(mv).unwrap();
+                    black_box(after);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn is_checkmate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("is_checkmate");
+    let mut boards = positions();
+    boards.push(("checkmate", Board::from_fen(CHECKMATE).unwrap()));
+    for (name, board) in boards {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &board, |b, board| {
+            b.iter(|| black_box(board).is_checkmate())
+        });
+    }
+    group.finish();
+}
+
+fn perft_depth_3(c: &mut Criterion) {
+    // One thread and no table, so only the move generation is measured
+    let options = PerftOptions {
+        threads: 1,
+        split_second_ply: false,
+        hash: false,
+    };
+    let mut group = c.benchmark_group("perft_3");
+    group.sample_size(10);
+    for (name, board) in positions() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &board, |b, board| {
+            b.iter(|| perft(black_box(board), 3, &options))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    generate_legal_moves,
+    is_legal,
+    make_move,
+    is_checkmate,
+    perft_depth_3
+);
+criterion_main!(benches);
@@ -0,0 +1,27 @@
+/*
+ * Regenerates include/chessapi.h from src/ffi.rs when the header feature is
+ * enabled, e.g. `cargo build --features header`. The header is committed, so
+ * building the C library doesn't need cbindgen.
+ */
+#[cfg(feature = "header")]
+extern crate cbindgen;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "header")]
+    header();
+}
+
+#[cfg(feature = "header")]
+fn header() {
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", dir)).unwrap();
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(format!("{}/src/ffi.rs", dir))
+        .generate()
+        .expect("src/ffi.rs should translate to C")
+        .write_to_file(format!("{}/include/chessapi.h", dir));
+}
@@ -0,0 +1,18 @@
+// Any text that parses as FEN has to give a position that survives the rules checks and writes back the same
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate ChessAPI;
+
+use ChessAPI::board::Board;
+use ChessAPI::invariants::check_position;
+
+fuzz_target!(|data: &[u8]| {
+    let board = match std::str::from_utf8(data).map(Board::from_fen) {
+        Ok(Ok(board)) => board,
+        _ => return,
+    };
+    let fen = board.to_fen();
+    assert_eq!(Board::from_fen(&fen).unwrap().to_fen(), fen);
+    check_position(&board);
+});
@@ -0,0 +1,21 @@
+// Plays a game where each byte of the input picks the next move, checking every position on the way
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate ChessAPI;
+
+use ChessAPI::game::Game;
+use ChessAPI::invariants::check_position;
+
+fuzz_target!(|data: &[u8]| {
+    let mut game = Game::new();
+    for &choice in data {
+        check_position(game.board());
+        let moves = game.board().generate_legal_moves();
+        if game.is_over() || moves.is_empty() {
+            break;
+        }
+        game.make_move(&moves[choice as usize % moves.len()])
+            .unwrap();
+    }
+});
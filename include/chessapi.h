@@ -0,0 +1,120 @@
+/*
+ * C interface to the ChessAPI rules, implemented in src/ffi.rs and built as
+ * the cdylib of the crate (libChessAPI.so, libChessAPI.dylib or ChessAPI.dll).
+ * Generated by cbindgen, run `cargo build --features header` after changing it.
+ *
+ * Squares are numbered from 0 for a1 to 63 for h8, i.e. rank * 8 + file.
+ * Every pointer may be null unless stated otherwise, a null board is treated
+ * as an error (CHESS_ERROR_NULL_POINTER) or as an empty result.
+ *
+ * A panic never unwinds into the caller. Functions that return a ChessError
+ * return CHESS_ERROR_INTERNAL instead, the others 0, false, a null pointer,
+ * CHESS_STATUS_ONGOING or CHESS_COLOR_WHITE.
+ */
+
+#ifndef CHESSAPI_H
+#define CHESSAPI_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// Promotion of a ChessMove, the pieces a pawn can become
+#define CHESS_PROMOTION_NONE 0
+
+#define CHESS_PROMOTION_KNIGHT 1
+
+#define CHESS_PROMOTION_BISHOP 2
+
+#define CHESS_PROMOTION_ROOK 3
+
+#define CHESS_PROMOTION_QUEEN 4
+
+// CHESS_ERROR_OK, then one code for each BoardError in the same order
+typedef enum {
+  CHESS_ERROR_OK = 0,
+  CHESS_ERROR_ILLEGAL_MOVE,
+  CHESS_ERROR_INVALID_SQUARE,
+  CHESS_ERROR_INVALID_KING_COUNT,
+  CHESS_ERROR_PAWN_ON_BACK_RANK,
+  CHESS_ERROR_OPPONENT_IN_CHECK,
+  CHESS_ERROR_INVALID_CASTLING_RIGHTS,
+  CHESS_ERROR_INVALID_EN_PASSANT,
+  CHESS_ERROR_INVALID_FEN,
+  CHESS_ERROR_INVALID_MOVE_NOTATION,
+  CHESS_ERROR_AMBIGUOUS_MOVE,
+  CHESS_ERROR_GAME_OVER,
+  CHESS_ERROR_INVALID_PGN,
+  CHESS_ERROR_INVALID_MATERIAL,
+  CHESS_ERROR_NULL_POINTER = -1,
+  // A bug in ChessAPI, the call panicked
+  CHESS_ERROR_INTERNAL = -2,
+} ChessError;
+
+typedef enum {
+  CHESS_STATUS_ONGOING,
+  CHESS_STATUS_CHECK,
+  // The player that made the last move won
+  CHESS_STATUS_CHECKMATE,
+  CHESS_STATUS_STALEMATE,
+  CHESS_STATUS_SEVENTY_FIVE_MOVE_RULE,
+  CHESS_STATUS_INSUFFICIENT_MATERIAL,
+  CHESS_STATUS_THREEFOLD_REPETITION,
+} ChessStatus;
+
+typedef enum {
+  CHESS_COLOR_WHITE,
+  CHESS_COLOR_BLACK,
+} ChessColor;
+
+// A game from a position on, including the moves made since, so they can be taken back
+typedef struct ChessBoard ChessBoard;
+
+typedef struct {
+  uint8_t from;
+  uint8_t to;
+  uint8_t promotion;
+} ChessMove;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// A board with the starting position, free it with chess_board_free()
+ChessBoard *chess_board_new(void);
+
+void chess_board_free(ChessBoard *board);
+
+// Replaces the position and forgets the moves made, the board is unchanged on errors
+ChessError chess_board_load_fen(ChessBoard *board, const char *fen);
+
+// Writes the legal moves into moves, at most capacity of them, and returns how
+// many there are. Call it with a null moves to find out how large it has to be.
+size_t chess_board_legal_moves(const ChessBoard *board, ChessMove *moves, size_t capacity);
+
+// Parses a legal move in SAN, e.g. "Nf3", or UCI notation, e.g. "g1f3", into
+// mv. Returns CHESS_ERROR_ILLEGAL_MOVE for a move that can't be made now.
+ChessError chess_board_parse_move(const ChessBoard *board, const char *notation, ChessMove *mv);
+
+ChessError chess_board_make_move(ChessBoard *board, ChessMove mv);
+
+// Takes back the last move into mv, which may be null, and returns false if there was none
+bool chess_board_unmake_move(ChessBoard *board, ChessMove *mv);
+
+ChessStatus chess_board_status(const ChessBoard *board);
+
+ChessColor chess_board_turn(const ChessBoard *board);
+
+// Writes the FEN of the position into buffer like snprintf(): at most
+// capacity - 1 characters and a terminating zero. Returns the length of the
+// whole FEN, so a result >= capacity means it was cut short.
+size_t chess_board_fen(const ChessBoard *board, char *buffer, size_t capacity);
+
+// The SAN of a legal move, e.g. "exd8=Q+", written like chess_board_fen(). 0 for illegal moves
+size_t chess_board_move_san(const ChessBoard *board, ChessMove mv, char *buffer, size_t capacity);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CHESSAPI_H */
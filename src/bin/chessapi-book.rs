@@ -0,0 +1,79 @@
+/*
+ * Builds an opening book from PGN files, either as a Polyglot .bin book
+ * or in ChessAPI's own format that keeps the wins, draws and losses.
+ */
+extern crate ChessAPI;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::process;
+
+use ChessAPI::book::{BookBuilder, DEFAULT_MAX_PLY};
+
+const USAGE: &str = "Usage: chessapi-book [options] -o <book> <games.pgn>...
+
+Options:
+  -o, --output <file>  Where to write the book
+  --plies <count>      How many half moves of each game to use (default 24)
+  --native             Write ChessAPI's own format instead of a Polyglot book
+  --help               Show this message";
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn main() {
+    let mut output = None;
+    let mut plies = DEFAULT_MAX_PLY;
+    let mut native = false;
+    let mut inputs = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                output = Some(args.next().unwrap_or_else(|| fail("--output needs a file")))
+            }
+            "--plies" => {
+                plies = args
+                    .next()
+                    .and_then(|plies| plies.parse().ok())
+                    .unwrap_or_else(|| fail("--plies needs a number"))
+            }
+            "--native" => native = true,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with('-') => fail(&format!("unknown option '{}'", arg)),
+            _ => inputs.push(arg),
+        }
+    }
+    let output = output.unwrap_or_else(|| fail("no output file given"));
+    if inputs.is_empty() {
+        fail("no PGN files given");
+    }
+
+    let mut builder = BookBuilder::new().max_ply(plies);
+    for input in inputs.iter() {
+        let pgn = fs::read(input).unwrap_or_else(|err| {
+            eprintln!("Can't read {}: {}", input, err);
+            process::exit(1);
+        });
+        // Old archives are often Latin-1, which only matters for the tags
+        let games = builder.add_pgn(&String::from_utf8_lossy(&pgn));
+        eprintln!("{}: {} games", input, games);
+    }
+
+    let result = File::create(&output).and_then(|file| match native {
+        true => builder.write_native(BufWriter::new(file)),
+        false => builder.write_polyglot(BufWriter::new(file)),
+    });
+    if let Err(err) = result {
+        eprintln!("Can't write {}: {}", output, err);
+        process::exit(1);
+    }
+    eprintln!("{} positions written to {}", builder.len(), output);
+}
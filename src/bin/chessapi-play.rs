@@ -0,0 +1,287 @@
+/*
+ * Play chess in the terminal, either two humans at the same keyboard or a
+ * human against the built-in engine. Moves are entered in SAN ("Nf3") or
+ * UCI ("g1f3"), type "help" for the other commands.
+ */
+extern crate ChessAPI;
+
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ChessAPI::board::{Board, BoardError};
+use ChessAPI::game::{Game, GameResult, Termination};
+use ChessAPI::piece::Color;
+use ChessAPI::render::{text, Glyphs, TextOptions};
+use ChessAPI::search::{search, SearchOptions, SkillLevel};
+
+const USAGE: &str = "Usage: chessapi-play [options]
+
+Options:
+  --engine <white|black>  Let the engine play that color
+  --depth <plies>         How deep the engine searches (default 3)
+  --threads <count>       How many threads the engine searches with (default 1)
+  --skill <0-20>          Let the engine play weaker, 0 is a beginner (default 20)
+  --fen <fen>             Start from this position instead of the initial one
+  --ascii                 Draw the pieces as letters instead of chess symbols
+  --no-color              Don't use ANSI colors for the board
+  --help                  Show this message";
+
+const HELP: &str = "Enter moves in SAN (e4, Nf3, O-O, e8=Q) or UCI (e2e4, g1f3, e1g1, e7e8q).
+
+Commands:
+  undo    Take back the last move, or your last move when playing the engine
+  flip    Turn the board around
+  moves   List the legal moves
+  fen     Print the position as FEN
+  pgn     Print the game as PGN
+  resign  Resign the game
+  draw    Claim a draw after 50 moves without a capture or pawn move,
+          otherwise offer one, the engine accepts if it isn't better
+  help    Show this message
+  quit    Leave";
+
+struct Options {
+    engine: Option<Color>,
+    depth: u32,
+    threads: usize,
+    skill: SkillLevel,
+    board: Board,
+    text: TextOptions,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        engine: None,
+        depth: 3,
+        threads: 1,
+        skill: SkillLevel::MAX,
+        board: Board::new(),
+        text: TextOptions {
+            glyphs: Glyphs::Unicode,
+            ansi_colors: true,
+            ..Default::default()
+        },
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+        match arg.as_str() {
+            "--engine" => {
+                options.engine = Some(match value("--engine")?.as_str() {
+                    "white" => Color::White,
+                    "black" => Color::Black,
+                    other => return Err(format!("unknown color '{}'", other)),
+                })
+            }
+            "--depth" => {
+                options.depth = value("--depth")?
+                    .parse()
+                    .map_err(|_| "--depth needs a number".to_string())?
+            }
+            "--threads" => {
+                options.threads = value("--threads")?
+                    .parse()
+                    .map_err(|_| "--threads needs a number".to_string())?
+            }
+            "--skill" => {
+                options.skill = SkillLevel::new(
+                    value("--skill")?
+                        .parse()
+                        .map_err(|_| "--skill needs a number".to_string())?,
+                )
+            }
+            "--fen" => {
+                options.board = Board::from_fen(&value("--fen")?)
+                    .map_err(|err| format!("invalid position: {}", err))?
+            }
+            "--ascii" => options.text.glyphs = Glyphs::Ascii,
+            "--no-color" => options.text.ansi_colors = false,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+    // Humans want to see the board from their own side
+    if options.engine == Some(Color::White) {
+        options.text.orientation = Color::Black;
+    }
+    Ok(options)
+}
+
+fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "White",
+        Color::Black => "Black",
+    }
+}
+
+fn describe_result(result: &GameResult) -> String {
+    let reason = match result.termination {
+        Termination::Checkmate => "checkmate",
+        Termination::Stalemate => "stalemate",
+        Termination::FiftyMoveRule => "the fifty move rule",
+        Termination::SeventyFiveMoveRule => "the seventy-five move rule",
+        Termination::InsufficientMaterial => "insufficient material",
+        Termination::ThreefoldRepetition => "threefold repetition",
+        Termination::Resignation => "resignation",
+        Termination::Agreement => "agreement",
+        Termination::Timeout => "timeout",
+    };
+    match result.winner {
+        Some(winner) => format!(
+            "{} {} wins by {}",
+            result.to_pgn_str(),
+            color_name(winner),
+            reason
+        ),
+        None => format!("{} Draw by {}", result.to_pgn_str(), reason),
+    }
+}
+
+// Explains why a move was rejected as precisely as possible
+fn rejection(board: &Board, input: &str, err: BoardError) -> String {
+    if err == BoardError::IllegalMove {
+        if let Ok(mv) = input.parse() {
+            if let Some(reason) = board.illegal_move_reason(&mv) {
+                return format!("Illegal move: {}", reason);
+            }
+        }
+        return "Illegal move: no piece can make that move".to_string();
+    }
+    format!("Can't play '{}': {}", input, err)
+}
+
+fn main() {
+    let mut options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+    let search_options = SearchOptions {
+        depth: options.depth,
+        threads: options.threads,
+        skill: Some(options.skill),
+        // A different game every time
+        seed: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64),
+        ..Default::default()
+    };
+
+    let mut game = Game::from_board(options.board);
+    if let Some(engine) = options.engine {
+        game.set_tag(color_name(engine), "ChessAPI");
+    }
+    println!("Type 'help' for the commands.\n");
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut show_board = true;
+
+    loop {
+        let board = *game.board();
+        let turn = board.whose_turn();
+
+        if show_board {
+            options.text.last_move = game.last_move();
+            println!("{}", text(&board, &options.text));
+            if let Some(result) = game.result() {
+                println!("{}\n", describe_result(&result));
+            }
+        }
+        show_board = true;
+
+        if !game.is_over() && options.engine == Some(turn) {
+            let result = search(&board, &search_options);
+            let mv = result.best_move.unwrap();
+            println!("{} plays {}\n", color_name(turn), board.move_to_san(&mv));
+            game.make_move(&mv).unwrap();
+            continue;
+        }
+
+        print!("{}> ", color_name(turn));
+        io::stdout().flush().unwrap();
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break,
+        };
+        let input = line.trim();
+
+        match input {
+            "" => show_board = false,
+            "quit" | "exit" => break,
+            "help" => {
+                println!("{}\n", HELP);
+                show_board = false;
+            }
+            "undo" => {
+                // Against the engine the human's last move is two moves back
+                let count = match options.engine {
+                    Some(engine) if engine != turn => 2,
+                    _ => 1,
+                };
+                if game.moves().is_empty() {
+                    println!("There is nothing to undo.\n");
+                    show_board = false;
+                }
+                for _ in 0..count {
+                    game.undo();
+                }
+            }
+            "flip" => options.text.orientation = options.text.orientation.flip(),
+            "moves" => {
+                let moves: Vec<String> = board
+                    .generate_legal_moves()
+                    .iter()
+                    .map(|mv| board.move_to_san(mv))
+                    .collect();
+                println!("{}\n", moves.join(" "));
+                show_board = false;
+            }
+            "fen" => {
+                println!("{}\n", board.to_fen());
+                show_board = false;
+            }
+            "pgn" => {
+                println!("{}", game.to_pgn());
+                show_board = false;
+            }
+            "resign" => game.resign(turn),
+            "draw" if board.can_claim_fifty_move_draw() => {
+                game.claim_draw();
+            }
+            "draw" => match options.engine {
+                Some(engine) => {
+                    // The score is from the side to move's point of view
+                    let score = search(&board, &search_options).score;
+                    let engine_score = if engine == turn { score } else { -score };
+                    if engine_score <= 0 {
+                        game.agree_draw();
+                    } else {
+                        println!("The engine declines the draw.\n");
+                        show_board = false;
+                    }
+                }
+                None => game.agree_draw(),
+            },
+            _ if game.is_over() => {
+                println!("The game is over, use 'undo' or 'quit'.\n");
+                show_board = false;
+            }
+            _ => {
+                let result = board.parse_move(input).and_then(|mv| game.make_move(&mv));
+                if let Err(err) = result {
+                    println!("{}\n", rejection(&board, input, err));
+                    show_board = false;
+                }
+            }
+        }
+    }
+}
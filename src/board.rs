@@ -1,18 +1,34 @@
 // This needs to be a macro, otherwise the borrow checker goes bonkers
+// Accepts anything that converts into a Position, e.g. a Square
 macro_rules! at {
     ($self:expr, $pos:expr) => {
-        $self.board[$pos.row as usize][$pos.col as usize]
+        $self.board[$crate::board::Position::from($pos)]
     };
 }
 
+pub mod builder;
 pub mod default;
+pub mod fen;
 pub mod generate_moves;
 mod is_attacked;
 pub mod is_legal;
-
+pub mod pins;
+pub mod polyglot;
+pub mod random;
+pub mod san;
+pub mod status;
+pub mod uci;
+
+pub use self::builder::*;
+// Only impl blocks for now, re-exported so that board:: reaches whatever is added there
+#[allow(unused_imports)]
 pub use self::default::*;
+pub use self::fen::*;
+#[allow(unused_imports)]
 pub use self::generate_moves::*;
 pub use self::is_legal::*;
+pub use self::pins::*;
+pub use self::status::*;
 pub const BOARD_ROW_COUNT: usize = 8;
 pub const BOARD_COL_COUNT: usize = 8;
 pub const BLACK_PIECE_STARTING_ROW: i8 = 0;
@@ -53,7 +69,11 @@ extern crate num_traits;
 
 use self::num_traits::*;
 use crate::piece::{Color, Piece, PieceType};
-use std::mem;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use square::Square;
+use std::error::Error;
+use std::fmt;
 use std::ops::*;
 
 // None => empty square, Some(Piece) => square occupied by Piece
@@ -64,9 +84,28 @@ pub type MutSquareType<'a> = Option<&'a mut Piece>;
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum BoardError {
     IllegalMove,
+    // Text that doesn't describe a square, or a Position outside of the board
+    InvalidSquare,
+    // Positions rejected by BoardBuilder::build()
+    InvalidKingCount,
+    PawnOnBackRank,
+    OpponentInCheck,
+    InvalidCastlingRights,
+    InvalidEnPassant,
+    // Text that isn't a valid FEN string or UCI move
+    InvalidFen,
+    InvalidMoveNotation,
+    // SAN that fits more than one legal move, e.g. "Nd2" when both knights can go there
+    AmbiguousMove,
+    // Moves made in a Game that has already ended
+    GameOver,
+    // PGN with a broken tag pair or without any game
+    InvalidPgn,
+    // Material signatures of tablebases that can't be parsed, e.g. "KQ" or "KQQQK"
+    InvalidMaterial,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Board {
     // The representation of the board. Value is Some(Piece) if occupied, otherwise None
     // Public in lib.rs for debugging purposes
@@ -85,15 +124,43 @@ pub struct Board {
     // The column that you are able to do en passant to, or -1 if unavailable
     en_passant_col: i8,
 
+    // Half moves since the last capture or pawn move, used for the fifty move rule
+    halfmove_clock: u32,
+    // Starts at 1 and is incremented after every move by black
+    fullmove_number: u32,
+
     turn: Color,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Position {
     pub row: i8,
     pub col: i8,
 }
 
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            BoardError::IllegalMove => "illegal move",
+            BoardError::InvalidSquare => "invalid square",
+            BoardError::InvalidKingCount => "each player needs exactly one king",
+            BoardError::PawnOnBackRank => "pawns can't stand on the first or last rank",
+            BoardError::OpponentInCheck => "the player that just moved is in check",
+            BoardError::InvalidCastlingRights => "castling rights without king and rook in place",
+            BoardError::InvalidEnPassant => "no pawn just moved past the en passant square",
+            BoardError::InvalidFen => "invalid FEN",
+            BoardError::InvalidMoveNotation => "invalid move notation",
+            BoardError::AmbiguousMove => "ambiguous move",
+            BoardError::GameOver => "the game is over",
+            BoardError::InvalidPgn => "invalid PGN",
+            BoardError::InvalidMaterial => "invalid material signature",
+        })
+    }
+}
+
+impl Error for BoardError {}
+
 impl Position {
     #[inline]
     fn abs(&self) -> Self {
@@ -151,6 +218,50 @@ impl Position {
             || self.row < 0
             || self.row >= BOARD_ROW_COUNT as i8
     }
+
+    // Returns None if the position is outside of the board
+    #[inline]
+    pub fn to_square(self) -> Option<Square> {
+        Square::new(self.col, BOARD_ROW_COUNT as i8 - 1 - self.row)
+    }
+}
+
+impl<'a> From<&'a Position> for Position {
+    #[inline]
+    fn from(pos: &'a Position) -> Self {
+        *pos
+    }
+}
+
+// Makes it possible to write get_board()[Square::E4] as well as get_board()[pos]
+impl Index<Square> for [[SquareType; 8]; 8] {
+    type Output = SquareType;
+    #[inline]
+    fn index(&self, square: Square) -> &Self::Output {
+        &self[Position::from(square)]
+    }
+}
+
+impl IndexMut<Square> for [[SquareType; 8]; 8] {
+    #[inline]
+    fn index_mut(&mut self, square: Square) -> &mut Self::Output {
+        &mut self[Position::from(square)]
+    }
+}
+
+impl Index<Position> for [[SquareType; 8]; 8] {
+    type Output = SquareType;
+    #[inline]
+    fn index(&self, pos: Position) -> &Self::Output {
+        &self[pos.row as usize][pos.col as usize]
+    }
+}
+
+impl IndexMut<Position> for [[SquareType; 8]; 8] {
+    #[inline]
+    fn index_mut(&mut self, pos: Position) -> &mut Self::Output {
+        &mut self[pos.row as usize][pos.col as usize]
+    }
 }
 
 // Pass this struct as an argument when you want to make a move
@@ -162,6 +273,22 @@ pub struct Move {
     pub promotion: Option<PieceType>,
 }
 
+impl Move {
+    // Accepts both Squares and Positions, e.g. Move::new(Square::E2, Square::E4, None)
+    #[inline]
+    pub fn new<F: Into<Position>, T: Into<Position>>(
+        from: F,
+        to: T,
+        promotion: Option<PieceType>,
+    ) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            promotion,
+        }
+    }
+}
+
 impl Board {
     // Generates a new board with the default starting position
     #[inline]
@@ -170,11 +297,29 @@ impl Board {
     }
     #[inline]
     pub fn get_board(&self) -> [[SquareType; 8]; 8] {
-        self.board.clone()
+        self.board
+    }
+
+    // The piece on a Square or Position, None if it is empty or outside of the board
+    #[inline]
+    pub fn piece_at<P: Into<Position>>(&self, pos: P) -> SquareType {
+        let pos = pos.into();
+        if pos.out_of_bounds() {
+            return None;
+        }
+        at!(self, pos)
     }
 
     pub fn whose_turn(&self) -> Color {
-        self.turn.clone()
+        self.turn
+    }
+
+    #[inline]
+    pub(crate) fn king_pos(&self, color: Color) -> Position {
+        match color {
+            Color::White => self.white_king_pos,
+            Color::Black => self.black_king_pos,
+        }
     }
 
     /*
@@ -191,63 +336,26 @@ impl Board {
     }
 
     pub fn is_stalemate(&self) -> bool {
-        self.generate_legal_moves().is_empty()
-            && !self.is_attacked_by_opponent(&match self.turn {
-                Color::White => self.white_king_pos,
-                Color::Black => self.black_king_pos,
-            })
+        !self.is_in_check() && !self.has_legal_moves()
     }
 
+    // Same as is_in_check(), the player might be checkmated
     pub fn is_check(&self) -> bool {
-        !self.generate_legal_moves().is_empty()
-            && self.is_attacked_by_opponent(&match self.turn {
-                Color::White => self.white_king_pos,
-                Color::Black => self.black_king_pos,
-            })
+        self.is_in_check()
     }
 
     pub fn is_checkmate(&self) -> bool {
-        self.generate_legal_moves().is_empty()
-            && self.is_attacked_by_opponent(&match self.turn {
-                Color::White => self.white_king_pos,
-                Color::Black => self.black_king_pos,
-            })
+        self.is_in_check() && !self.has_legal_moves()
     }
 
+    // Handy when debugging, the tests use it to show the games they play
+    #[allow(dead_code)]
     pub(crate) fn print_board(&self) {
-        for row in 0..BOARD_ROW_COUNT {
-            for col in 0..BOARD_COL_COUNT {
-                print!(
-                    "{}",
-                    self.board[row][col]
-                        .as_ref()
-                        .map_or("·", |piece| match piece.color {
-                            Color::White => match piece.piece_type {
-                                PieceType::Pawn => "P",
-                                PieceType::Rook => "R",
-                                PieceType::Knight => "N",
-                                PieceType::Bishop => "B",
-                                PieceType::Queen => "Q",
-                                PieceType::King => "K",
-                            },
-                            Color::Black => match piece.piece_type {
-                                PieceType::Pawn => "p",
-                                PieceType::Rook => "r",
-                                PieceType::Knight => "n",
-                                PieceType::Bishop => "b",
-                                PieceType::Queen => "q",
-                                PieceType::King => "k",
-                            },
-                        })
-                );
-            }
-            println!();
-        }
-        println!();
+        println!("{}", self);
     }
 
     // Makes a move without checking if it's legal
-    fn unsafe_make_move(&mut self, mv: &Move) -> Result<(), BoardError> {
+    pub(crate) fn unsafe_make_move(&mut self, mv: &Move) -> Result<(), BoardError> {
         self.turn = self.turn.flip();
 
         // Reset en passant
@@ -258,6 +366,15 @@ impl Board {
             Some(piece) => piece,
         };
 
+        if piece.color == Color::Black {
+            self.fullmove_number += 1;
+        }
+        if piece.piece_type == PieceType::Pawn || at!(self, mv.to).is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
         match mv.to {
             WHITE_ROOK_SHORT_STARTING_POS => self.short_castle_white = false,
             WHITE_ROOK_LONG_STARTING_POS => self.long_castle_white = false,
@@ -317,25 +434,23 @@ impl Board {
                 // Move the rook if castling
                 if (mv.from.col - mv.to.col).abs() >= 2 {
                     let dir = (mv.to.col - mv.from.col) / (mv.to.col - mv.from.col).abs();
-                    let mut rook_pos = mv.from.clone();
+                    let mut rook_pos = mv.from;
                     rook_pos.col += dir;
 
-                    at!(self, rook_pos) = mem::replace(
-                        &mut at!(
-                            self,
-                            match dir > 0 {
-                                true => Position {
-                                    row: mv.from.row,
-                                    col: ROOK_SHORT_STARTING_COL,
-                                },
-                                false => Position {
-                                    row: mv.from.row,
-                                    col: ROOK_LONG_STARTING_COL,
-                                },
-                            }
-                        ),
-                        None,
+                    at!(self, rook_pos) = at!(
+                        self,
+                        match dir > 0 {
+                            true => Position {
+                                row: mv.from.row,
+                                col: ROOK_SHORT_STARTING_COL,
+                            },
+                            false => Position {
+                                row: mv.from.row,
+                                col: ROOK_LONG_STARTING_COL,
+                            },
+                        }
                     )
+                    .take()
                 }
             }
 
@@ -343,12 +458,12 @@ impl Board {
         };
 
         // Move the piece into the new position, replacing it with None in the process
-        at!(self, mv.to) = mem::replace(&mut at!(self, mv.from), None);
+        at!(self, mv.to) = at!(self, mv.from).take();
         Ok(())
     }
 
     pub fn make_move(&mut self, mv: &Move) -> Result<(), BoardError> {
-        if self.is_legal(&mv) == false {
+        if !self.is_legal(mv) {
             return Err(BoardError::IllegalMove);
         }
         self.unsafe_make_move(mv)
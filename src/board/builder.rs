@@ -0,0 +1,261 @@
+use board::*;
+use piece::PieceType;
+use square::Square;
+
+/*
+ * Sets up arbitrary positions, e.g. endgames. Starts out with an empty board,
+ * White to move and neither castling nor en passant available. Use
+ * BoardBuilder::from(&board) to start from an existing position instead.
+ */
+#[derive(Debug, Clone)]
+pub struct BoardBuilder {
+    board: [[SquareType; 8]; 8],
+    turn: Color,
+    short_castle_white: bool,
+    long_castle_white: bool,
+    short_castle_black: bool,
+    long_castle_black: bool,
+    // The square a pawn skipped over with its last move
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self {
+            board: [[None; BOARD_COL_COUNT]; BOARD_ROW_COUNT],
+            turn: Color::White,
+            short_castle_white: false,
+            long_castle_white: false,
+            short_castle_black: false,
+            long_castle_black: false,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
+}
+
+impl<'a> From<&'a Board> for BoardBuilder {
+    fn from(board: &'a Board) -> Self {
+        Self {
+            board: board.board,
+            turn: board.turn,
+            short_castle_white: board.short_castle_white,
+            long_castle_white: board.long_castle_white,
+            short_castle_black: board.short_castle_black,
+            long_castle_black: board.long_castle_black,
+            en_passant: board.en_passant_square(),
+            halfmove_clock: board.halfmove_clock,
+            fullmove_number: board.fullmove_number,
+        }
+    }
+}
+
+impl BoardBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // Places piece on pos, replacing whatever stood there
+    pub fn piece<P: Into<Position>>(mut self, pos: P, piece: Piece) -> Self {
+        let pos = pos.into();
+        if !pos.out_of_bounds() {
+            at!(self, pos) = Some(piece);
+        }
+        self
+    }
+
+    pub fn remove<P: Into<Position>>(mut self, pos: P) -> Self {
+        let pos = pos.into();
+        if !pos.out_of_bounds() {
+            at!(self, pos) = None;
+        }
+        self
+    }
+
+    pub fn turn(mut self, turn: Color) -> Self {
+        self.turn = turn;
+        self
+    }
+
+    pub fn castling(mut self, color: Color, short: bool, long: bool) -> Self {
+        match color {
+            Color::White => {
+                self.short_castle_white = short;
+                self.long_castle_white = long;
+            }
+            Color::Black => {
+                self.short_castle_black = short;
+                self.long_castle_black = long;
+            }
+        }
+        self
+    }
+
+    // The square behind a pawn that just moved two steps, e.g. E3 after e2e4
+    pub fn en_passant(mut self, square: Option<Square>) -> Self {
+        self.en_passant = square;
+        self
+    }
+
+    pub fn halfmove_clock(mut self, halfmove_clock: u32) -> Self {
+        self.halfmove_clock = halfmove_clock;
+        self
+    }
+
+    pub fn fullmove_number(mut self, fullmove_number: u32) -> Self {
+        self.fullmove_number = fullmove_number;
+        self
+    }
+
+    /*
+     * Checks that the position could occur in a game: one king per side, no
+     * pawns on the first or last rank, the player that just moved not left in
+     * check, castling rights matching the king and rook placement and the en
+     * passant square matching a pawn that just moved two steps.
+     */
+    pub fn build(&self) -> Result<Board, BoardError> {
+        let white_king_pos = self.find_king(Color::White)?;
+        let black_king_pos = self.find_king(Color::Black)?;
+
+        for row in [WHITE_PIECE_STARTING_ROW, BLACK_PIECE_STARTING_ROW] {
+            if self.board[row as usize]
+                .iter()
+                .any(|square| square.map(|piece| piece.piece_type) == Some(PieceType::Pawn))
+            {
+                return Err(BoardError::PawnOnBackRank);
+            }
+        }
+
+        self.validate_castling()?;
+        let en_passant_col = self.validate_en_passant()?;
+
+        let board = Board {
+            board: self.board,
+            white_king_pos,
+            black_king_pos,
+            short_castle_white: self.short_castle_white,
+            long_castle_white: self.long_castle_white,
+            short_castle_black: self.short_castle_black,
+            long_castle_black: self.long_castle_black,
+            en_passant_col,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            turn: self.turn,
+        };
+
+        if board.is_attacked_by_player(&board.king_pos(self.turn.flip())) {
+            return Err(BoardError::OpponentInCheck);
+        }
+        Ok(board)
+    }
+
+    fn find_king(&self, color: Color) -> Result<Position, BoardError> {
+        let king = Some(Piece {
+            piece_type: PieceType::King,
+            color,
+        });
+        let mut kings = Vec::new();
+        for row in 0..BOARD_ROW_COUNT {
+            for col in 0..BOARD_COL_COUNT {
+                if self.board[row][col] == king {
+                    kings.push(Position {
+                        row: row as i8,
+                        col: col as i8,
+                    });
+                }
+            }
+        }
+        match kings.len() {
+            1 => Ok(kings[0]),
+            _ => Err(BoardError::InvalidKingCount),
+        }
+    }
+
+    fn validate_castling(&self) -> Result<(), BoardError> {
+        let rights = [
+            (
+                self.short_castle_white,
+                Color::White,
+                WHITE_KING_STARTING_POS,
+                WHITE_ROOK_SHORT_STARTING_POS,
+            ),
+            (
+                self.long_castle_white,
+                Color::White,
+                WHITE_KING_STARTING_POS,
+                WHITE_ROOK_LONG_STARTING_POS,
+            ),
+            (
+                self.short_castle_black,
+                Color::Black,
+                BLACK_KING_STARTING_POS,
+                BLACK_ROOK_SHORT_STARTING_POS,
+            ),
+            (
+                self.long_castle_black,
+                Color::Black,
+                BLACK_KING_STARTING_POS,
+                BLACK_ROOK_LONG_STARTING_POS,
+            ),
+        ];
+        for (right, color, king_pos, rook_pos) in rights {
+            let in_place = at!(self, king_pos)
+                == Some(Piece {
+                    piece_type: PieceType::King,
+                    color,
+                })
+                && at!(self, rook_pos)
+                    == Some(Piece {
+                        piece_type: PieceType::Rook,
+                        color,
+                    });
+            if right && !in_place {
+                return Err(BoardError::InvalidCastlingRights);
+            }
+        }
+        Ok(())
+    }
+
+    // Returns the en passant column the way Board stores it
+    fn validate_en_passant(&self) -> Result<i8, BoardError> {
+        let square = match self.en_passant {
+            Some(square) => square,
+            None => return Ok(-1),
+        };
+        // The rank the pawn skipped over, seen from White's side
+        let (rank, dir) = match self.turn {
+            Color::White => (5, -1),
+            Color::Black => (2, 1),
+        };
+        let pawn = Some(Piece {
+            piece_type: PieceType::Pawn,
+            color: self.turn.flip(),
+        });
+
+        if square.rank() != rank {
+            return Err(BoardError::InvalidEnPassant);
+        }
+        // The pawn must stand in front of the square and have come from behind it
+        let pawn_square = square.offset(0, dir).unwrap();
+        let from_square = square.offset(0, -dir).unwrap();
+        if at!(self, square).is_some()
+            || at!(self, from_square).is_some()
+            || at!(self, pawn_square) != pawn
+        {
+            return Err(BoardError::InvalidEnPassant);
+        }
+        Ok(square.file())
+    }
+}
+
+impl Board {
+    // Shorthand for BoardBuilder::new()
+    #[inline]
+    pub fn builder() -> BoardBuilder {
+        BoardBuilder::new()
+    }
+}
@@ -101,6 +101,9 @@ impl Default for Board {
 
             en_passant_col: -1,
 
+            halfmove_clock: 0,
+            fullmove_number: 1,
+
             turn: Color::White,
         }
     }
@@ -0,0 +1,171 @@
+use board::*;
+use square::Square;
+use std::str::FromStr;
+
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+impl Board {
+    /*
+     * Parses a position in Forsyth-Edwards Notation. The move counters may be
+     * left out, in which case they default to 0 and 1. The position is
+     * validated the same way as BoardBuilder::build() does.
+     */
+    pub fn from_fen(fen: &str) -> Result<Board, BoardError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 || fields.len() > 6 {
+            return Err(BoardError::InvalidFen);
+        }
+
+        let mut builder = BoardBuilder::new();
+
+        let rows: Vec<&str> = fields[0].split('/').collect();
+        if rows.len() != BOARD_ROW_COUNT {
+            return Err(BoardError::InvalidFen);
+        }
+        // FEN starts with the eighth rank, just like row 0
+        for (row, pieces) in rows.iter().enumerate() {
+            let mut col = 0;
+            for c in pieces.chars() {
+                if let Some(empty) = c.to_digit(10) {
+                    col += empty as usize;
+                    continue;
+                }
+                let piece = Piece::from_char(c).ok_or(BoardError::InvalidFen)?;
+                if col >= BOARD_COL_COUNT {
+                    return Err(BoardError::InvalidFen);
+                }
+                builder = builder.piece(
+                    Position {
+                        row: row as i8,
+                        col: col as i8,
+                    },
+                    piece,
+                );
+                col += 1;
+            }
+            if col != BOARD_COL_COUNT {
+                return Err(BoardError::InvalidFen);
+            }
+        }
+
+        builder = builder.turn(match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err(BoardError::InvalidFen),
+        });
+
+        if fields[2] != "-" {
+            let mut rights = [false; 4];
+            for c in fields[2].chars() {
+                let index = "KQkq".find(c).ok_or(BoardError::InvalidFen)?;
+                rights[index] = true;
+            }
+            builder = builder
+                .castling(Color::White, rights[0], rights[1])
+                .castling(Color::Black, rights[2], rights[3]);
+        }
+
+        if fields[3] != "-" {
+            let square = Square::from_algebraic(fields[3]).ok_or(BoardError::InvalidFen)?;
+            builder = builder.en_passant(Some(square));
+        }
+
+        if let Some(halfmove_clock) = fields.get(4) {
+            builder =
+                builder.halfmove_clock(halfmove_clock.parse().map_err(|_| BoardError::InvalidFen)?);
+        }
+        if let Some(fullmove_number) = fields.get(5) {
+            builder = builder.fullmove_number(
+                fullmove_number
+                    .parse()
+                    .map_err(|_| BoardError::InvalidFen)?,
+            );
+        }
+
+        builder.build()
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+
+        for (row, pieces) in self.board.iter().enumerate() {
+            let mut empty = 0;
+            for square in pieces.iter() {
+                match square {
+                    Some(piece) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece.to_char());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if row + 1 < BOARD_ROW_COUNT {
+                fen.push('/');
+            }
+        }
+
+        fen.push_str(match self.turn {
+            Color::White => " w ",
+            Color::Black => " b ",
+        });
+
+        let rights = [
+            (self.short_castle_white, 'K'),
+            (self.long_castle_white, 'Q'),
+            (self.short_castle_black, 'k'),
+            (self.long_castle_black, 'q'),
+        ];
+        let castling: String = rights
+            .iter()
+            .filter(|(right, _)| *right)
+            .map(|(_, c)| *c)
+            .collect();
+        match castling.is_empty() {
+            true => fen.push('-'),
+            false => fen.push_str(&castling),
+        }
+
+        match self.en_passant_square() {
+            Some(square) => fen.push_str(&format!(" {} ", square)),
+            None => fen.push_str(" - "),
+        }
+
+        fen.push_str(&format!("{} {}", self.halfmove_clock, self.fullmove_number));
+        fen
+    }
+
+    // Whether color may still castle (short, long), which doesn't mean that it is legal right now
+    pub fn castling_rights(&self, color: Color) -> (bool, bool) {
+        match color {
+            Color::White => (self.short_castle_white, self.long_castle_white),
+            Color::Black => (self.short_castle_black, self.long_castle_black),
+        }
+    }
+
+    // The square a pawn skipped over with the last move, if it moved two steps
+    pub fn en_passant_square(&self) -> Option<Square> {
+        match self.en_passant_col {
+            -1 => None,
+            col => Square::new(
+                col,
+                match self.turn {
+                    Color::White => 5,
+                    Color::Black => 2,
+                },
+            ),
+        }
+    }
+}
+
+impl FromStr for Board {
+    type Err = BoardError;
+    fn from_str(fen: &str) -> Result<Self, Self::Err> {
+        Board::from_fen(fen)
+    }
+}
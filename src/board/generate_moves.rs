@@ -6,10 +6,39 @@ impl Board {
         self.retain_legal(self.generate_moves())
     }
 
+    // Stops at the first legal move instead of generating all of them
+    pub(crate) fn has_legal_moves(&self) -> bool {
+        self.generate_moves().iter().any(|mv| self.is_legal(mv))
+    }
+
     pub fn generate_legal_capturing_moves(&self) -> Vec<Move> {
         self.retain_legal_capturing(self.generate_moves())
     }
 
+    // All legal moves of the piece standing on pos, empty if it can't move or isn't ours
+    pub fn legal_moves_from<P: Into<Position>>(&self, pos: P) -> Vec<Move> {
+        let pos = pos.into();
+        if pos.out_of_bounds() {
+            return Vec::new();
+        }
+        self.retain_legal(self.generate_moves_piece(&pos))
+    }
+
+    // All legal moves made by pieces of the given type, e.g. every knight move
+    pub fn legal_moves_of_type(&self, piece_type: PieceType) -> Vec<Move> {
+        let mut moves = self.generate_moves();
+        moves.retain(|mv| at!(self, mv.from).map(|piece| piece.piece_type) == Some(piece_type));
+        self.retain_legal(moves)
+    }
+
+    // All legal moves ending on pos, i.e. which pieces can go there
+    pub fn legal_moves_to<P: Into<Position>>(&self, pos: P) -> Vec<Move> {
+        let pos = pos.into();
+        let mut moves = self.generate_moves();
+        moves.retain(|mv| mv.to == pos);
+        self.retain_legal(moves)
+    }
+
     fn generate_moves(&self) -> Vec<Move> {
         let mut moves = Vec::new();
         for row in 0..BOARD_ROW_COUNT {
@@ -107,7 +136,7 @@ impl Board {
                 moves.push(Move {
                     from: *from,
                     to: position,
-                    promotion: promotion,
+                    promotion,
                 });
             }
         }
@@ -164,10 +193,10 @@ impl Board {
             };
             let mut to = *from + dpos;
 
-            while to.out_of_bounds() == false {
+            while !to.out_of_bounds() {
                 moves.push(Move {
                     from: *from,
-                    to: to,
+                    to,
                     promotion: None,
                 });
 
@@ -2,10 +2,98 @@ use board::*;
 use piece::PieceType;
 
 impl Board {
+    /*
+     * Positions of all pieces of by_color that attack pos, whether pos is
+     * occupied or not. A piece defending one of its own is counted as well,
+     * while pawns attacking en passant are not.
+     */
+    pub fn attackers_of<P: Into<Position>>(&self, pos: P, by_color: Color) -> Vec<Position> {
+        let pos = pos.into();
+        let mut attackers = Vec::new();
+        if pos.out_of_bounds() {
+            return attackers;
+        }
+
+        // A pawn attacks pos if it stands one step behind it, seen from the pawn's side
+        let pawn_row = pos.row - Board::dir(&by_color);
+        let pawns = [
+            Position {
+                row: pawn_row,
+                col: pos.col - 1,
+            },
+            Position {
+                row: pawn_row,
+                col: pos.col + 1,
+            },
+        ];
+        let mut kings = self.generate_moves_king(&pos);
+        // Castling doesn't attack anything
+        kings.retain(|mv| (mv.to.col - mv.from.col).abs() <= 1);
+
+        let candidates = [
+            (
+                pawns.iter().map(|to| Move::new(pos, *to, None)).collect(),
+                vec![PieceType::Pawn],
+            ),
+            (self.generate_moves_knight(&pos), vec![PieceType::Knight]),
+            (kings, vec![PieceType::King]),
+            (
+                self.generate_moves_adjacent(&pos),
+                vec![PieceType::Rook, PieceType::Queen],
+            ),
+            (
+                self.generate_moves_diagonal(&pos),
+                vec![PieceType::Bishop, PieceType::Queen],
+            ),
+        ];
+
+        for (moves, types) in candidates {
+            for mv in self.retain_capturing(moves) {
+                let piece = at!(self, mv.to).unwrap();
+                if piece.color == by_color && types.contains(&piece.piece_type) {
+                    attackers.push(mv.to);
+                }
+            }
+        }
+        attackers
+    }
+
+    // The pieces protecting the piece on pos, empty if the square is empty
+    pub fn defenders_of<P: Into<Position>>(&self, pos: P) -> Vec<Position> {
+        let pos = pos.into();
+        match self.piece_at(pos) {
+            Some(piece) => self.attackers_of(pos, piece.color),
+            None => Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn is_square_attacked<P: Into<Position>>(&self, pos: P, by_color: Color) -> bool {
+        !self.attackers_of(pos, by_color).is_empty()
+    }
+
+    /*
+     * The number of pieces of by_color attacking every square, indexed the same
+     * way as get_board(). Squares holding pieces of by_color count their defenders.
+     */
+    pub fn attack_map(&self, by_color: Color) -> [[u8; 8]; 8] {
+        let mut map = [[0; 8]; 8];
+        for (row, counts) in map.iter_mut().enumerate() {
+            for (col, count) in counts.iter_mut().enumerate() {
+                let pos = Position {
+                    row: row as i8,
+                    col: col as i8,
+                };
+                *count = self.attackers_of(pos, by_color).len() as u8;
+            }
+        }
+        map
+    }
+
     // Use this method when you want to check which pieces
     // the opponent will attack after you've made your move
     pub(in board) fn is_attacked_by_opponent(&self, pos: &Position) -> bool {
-        self.is_attacked(pos, &self.turn.clone().flip())
+        self.is_attacked(pos, &self.turn.flip())
     }
     // Use this method when you want to check which pieces
     // are currently under attack. E.g. white queen is under
@@ -29,7 +117,7 @@ impl Board {
         let mut moves = self.retain_capturing(self.generate_moves_knight(pos));
         moves.retain(|mv| at!(self, mv.to).as_ref().unwrap() == &opponent_knight);
 
-        moves.len() != 0
+        !moves.is_empty()
     }
 
     fn is_attacked_by_pawn(&self, pos: &Position, by_color: &Color) -> bool {
@@ -67,10 +155,7 @@ impl Board {
             if attack.out_of_bounds() {
                 continue;
             }
-            if at!(self, attack)
-                .as_ref()
-                .map_or(false, |piece| piece == &opponent_pawn)
-            {
+            if at!(self, attack).as_ref() == Some(&opponent_pawn) {
                 return true;
             }
         }
@@ -103,7 +188,7 @@ impl Board {
         &self,
         pos: &Position,
         dirs: &Vec<[i8; 2]>,
-        types: &Vec<PieceType>,
+        types: &[PieceType],
         by_color: &Color,
     ) -> bool {
         let mut moves = self.retain_capturing(self.generate_moves_line(pos, dirs));
@@ -114,6 +199,6 @@ impl Board {
                 .unwrap()
         });
 
-        moves.len() != 0
+        !moves.is_empty()
     }
 }
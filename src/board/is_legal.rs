@@ -1,5 +1,39 @@
 use board::*;
 use piece::PieceType;
+use std::fmt;
+
+// Why is_legal() rejected a move, see Board::illegal_move_reason()
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum IllegalMoveReason {
+    OutOfBounds,
+    NoPieceOnSquare,
+    NotYourPiece,
+    CaptureOwnPiece,
+    // A pawn reached the last rank without Move::promotion being set
+    MissingPromotion,
+    // Promotion to a pawn or king, or promotion set on a move that doesn't promote
+    InvalidPromotion,
+    CastlingNotAllowed,
+    // The piece doesn't move like that or another piece is in the way
+    CantMoveThere,
+    LeavesKingInCheck,
+}
+
+impl fmt::Display for IllegalMoveReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            IllegalMoveReason::OutOfBounds => "the move leaves the board",
+            IllegalMoveReason::NoPieceOnSquare => "there is no piece on that square",
+            IllegalMoveReason::NotYourPiece => "that piece belongs to the opponent",
+            IllegalMoveReason::CaptureOwnPiece => "you can't capture your own piece",
+            IllegalMoveReason::MissingPromotion => "the pawn has to promote",
+            IllegalMoveReason::InvalidPromotion => "invalid promotion",
+            IllegalMoveReason::CastlingNotAllowed => "castling isn't allowed right now",
+            IllegalMoveReason::CantMoveThere => "the piece can't move there",
+            IllegalMoveReason::LeavesKingInCheck => "the move leaves the king in check",
+        })
+    }
+}
 
 impl Board {
     #[inline]
@@ -17,7 +51,7 @@ impl Board {
         moves
     }
     fn is_own_king_attacked_after_move(&self, mv: &Move) -> bool {
-        let mut board_copy = self.clone();
+        let mut board_copy = *self;
         board_copy.unsafe_make_move(mv).unwrap();
 
         board_copy.is_attacked_by_player(&match self.turn {
@@ -32,7 +66,7 @@ impl Board {
         // If promoting move
         if mv.to.row == WHITE_PIECE_STARTING_ROW || mv.to.row == BLACK_PIECE_STARTING_ROW {
             // If disallowed promotion piece
-            if mv.promotion.as_ref().map_or(true, |piece_type| {
+            if mv.promotion.as_ref().is_none_or(|piece_type| {
                 *piece_type == PieceType::Pawn || *piece_type == PieceType::King
             }) {
                 return false;
@@ -51,7 +85,7 @@ impl Board {
 
         // 1 move forward
         if dcol == 0 && drow == dir {
-            return at!(self, mv.to) == None;
+            return at!(self, mv.to).is_none();
         }
 
         // 2 moves forward
@@ -62,15 +96,15 @@ impl Board {
             };
             // Return (pawn is on starting row) && (two squares in front are free)
             return start_row == mv.from.row
-                && at!(self, (mv.from + mv.to) / 2) == None
-                && at!(self, mv.to) == None;
+                && at!(self, (mv.from + mv.to) / 2).is_none()
+                && at!(self, mv.to).is_none();
         }
 
         // Capture (normal capture or en passant)
         if dcol == 1 && drow == dir {
             return at!(self, mv.to)
                 .as_ref()
-                .map_or(false, |p| p.color != self.turn)
+                .is_some_and(|p| p.color != self.turn)
                 || (self.en_passant_col == mv.to.col
                     && mv.from.row
                         == match self.turn {
@@ -86,10 +120,10 @@ impl Board {
         // Make sure that it moves 2 squares in one direction and 1 square in the other
         // and that the target square isn't occupied by a piece of the same color
         ((dpos.col == 1 && dpos.row == 2) || (dpos.col == 2 && dpos.row == 1))
-            && at!(self, mv.to).as_ref().map_or(
-                true, // Unoccupied square is fine :)
-                |target| target.color != self.turn,
-            )
+            && at!(self, mv.to)
+                .as_ref()
+                // Unoccupied square is fine :)
+                .is_none_or(|target| target.color != self.turn)
     }
 
     fn is_legal_rook(&self, mv: &Move) -> bool {
@@ -105,18 +139,18 @@ impl Board {
         // The difference in position that one step resemblances
         let step = dpos / steps;
 
-        let mut curr_pos = mv.from.clone();
+        let mut curr_pos = mv.from;
 
         for _ in 0..(steps - 1) {
             curr_pos += step;
-            if at!(self, curr_pos) != None {
+            if at!(self, curr_pos).is_some() {
                 return false;
             }
         }
 
         at!(self, mv.to)
             .as_ref()
-            .map_or(true, |piece| piece.color != self.turn)
+            .is_none_or(|piece| piece.color != self.turn)
     }
 
     fn is_legal_bishop(&self, mv: &Move) -> bool {
@@ -130,7 +164,7 @@ impl Board {
 
         let step = dpos / steps;
 
-        let mut curr_pos = mv.from.clone();
+        let mut curr_pos = mv.from;
 
         for _ in 0..(steps - 1) {
             curr_pos += step;
@@ -140,7 +174,7 @@ impl Board {
         }
         at!(self, mv.to)
             .as_ref()
-            .map_or(true, |piece| piece.color != self.turn)
+            .is_none_or(|piece| piece.color != self.turn)
     }
 
     fn is_legal_queen(&self, mv: &Move) -> bool {
@@ -155,7 +189,7 @@ impl Board {
         if dpos.col <= 1 && dpos.row <= 1 {
             return at!(self, mv.to)
                 .as_ref()
-                .map_or(true, |piece| piece.color != self.turn);
+                .is_none_or(|piece| piece.color != self.turn);
         }
 
         // Castling
@@ -186,27 +220,25 @@ impl Board {
             }
             // Short castle moves toward higher col counts
             let is_short_castle = (mv.to.col - mv.from.col) > 0;
-            let rook_pos: Position;
-
-            if is_short_castle {
+            let rook_pos = if is_short_castle {
                 if !short_castle {
                     return false;
                 }
-                rook_pos = match self.turn {
+                match self.turn {
                     Color::White => WHITE_ROOK_SHORT_STARTING_POS,
                     Color::Black => BLACK_ROOK_SHORT_STARTING_POS,
-                };
+                }
             } else
             /* is_long_castle */
             {
                 if !long_castle {
                     return false;
                 }
-                rook_pos = match self.turn {
+                match self.turn {
                     Color::White => WHITE_ROOK_LONG_STARTING_POS,
                     Color::Black => BLACK_ROOK_LONG_STARTING_POS,
-                };
-            }
+                }
+            };
             // get_moves_adjacent()
             // will return all possible moves without checking
             // if it is actually legal to capture your own king.
@@ -239,6 +271,10 @@ impl Board {
         if piece.color != self.turn {
             return false;
         }
+        // Only pawns promote
+        if piece.piece_type != PieceType::Pawn && mv.promotion.is_some() {
+            return false;
+        }
 
         (match piece.piece_type {
             PieceType::Pawn => self.is_legal_pawn(mv),
@@ -249,4 +285,59 @@ impl Board {
             PieceType::King => self.is_legal_king(mv),
         } && !self.is_own_king_attacked_after_move(mv))
     }
+
+    // Explains why a move isn't legal, returns None if it is
+    pub fn illegal_move_reason(&self, mv: &Move) -> Option<IllegalMoveReason> {
+        if self.is_legal(mv) {
+            return None;
+        }
+        if mv.from.out_of_bounds() || mv.to.out_of_bounds() {
+            return Some(IllegalMoveReason::OutOfBounds);
+        }
+        let piece = match at!(self, mv.from) {
+            Some(piece) => piece,
+            None => return Some(IllegalMoveReason::NoPieceOnSquare),
+        };
+        if piece.color != self.turn {
+            return Some(IllegalMoveReason::NotYourPiece);
+        }
+        if mv.from == mv.to {
+            return Some(IllegalMoveReason::CantMoveThere);
+        }
+        if at!(self, mv.to).is_some_and(|target| target.color == self.turn) {
+            return Some(IllegalMoveReason::CaptureOwnPiece);
+        }
+
+        // Whether the piece moves like that, with the promotion it would need
+        let promotes = piece.piece_type == PieceType::Pawn
+            && (mv.to.row == WHITE_PIECE_STARTING_ROW || mv.to.row == BLACK_PIECE_STARTING_ROW);
+        let shape = Move {
+            promotion: Some(PieceType::Queen).filter(|_| promotes),
+            ..*mv
+        };
+        let moves_like_that = match piece.piece_type {
+            PieceType::Pawn => self.is_legal_pawn(&shape),
+            PieceType::Rook => self.is_legal_rook(&shape),
+            PieceType::Knight => self.is_legal_knight(&shape),
+            PieceType::Bishop => self.is_legal_bishop(&shape),
+            PieceType::Queen => self.is_legal_queen(&shape),
+            PieceType::King => self.is_legal_king(&shape),
+        };
+        if !moves_like_that {
+            let dpos = (mv.to - mv.from).abs();
+            if piece.piece_type == PieceType::King && dpos.col == 2 && dpos.row == 0 {
+                return Some(IllegalMoveReason::CastlingNotAllowed);
+            }
+            return Some(IllegalMoveReason::CantMoveThere);
+        }
+
+        match (promotes, mv.promotion) {
+            (true, None) => Some(IllegalMoveReason::MissingPromotion),
+            (false, Some(_)) | (true, Some(PieceType::Pawn)) | (true, Some(PieceType::King)) => {
+                Some(IllegalMoveReason::InvalidPromotion)
+            }
+            // The move is fine apart from the king
+            _ => Some(IllegalMoveReason::LeavesKingInCheck),
+        }
+    }
 }
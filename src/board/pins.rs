@@ -0,0 +1,112 @@
+use board::*;
+use piece::PieceType;
+
+// A line piece that would attack target if blocker moved out of the way
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DiscoveredAttack {
+    pub attacker: Position,
+    pub blocker: Position,
+    pub target: Position,
+}
+
+const ADJACENT_DIRS: [[i8; 2]; 4] = [[1, 0], [0, 1], [-1, 0], [0, -1]];
+const DIAGONAL_DIRS: [[i8; 2]; 4] = [[1, 1], [-1, -1], [1, -1], [-1, 1]];
+
+impl Board {
+    // Positions of the opponent's pieces giving check to the player whose turn it is
+    pub fn checkers(&self) -> Vec<Position> {
+        self.attackers_of(self.king_pos(self.turn), self.turn.flip())
+    }
+
+    /*
+     * The pieces of color that can't leave the line between their king and an
+     * opponent's line piece without exposing the king, as (pinned piece, pinner)
+     */
+    pub fn pinned_pieces(&self, color: Color) -> Vec<(Position, Position)> {
+        let king_pos = self.king_pos(color);
+        let mut pins = Vec::new();
+
+        for (dir, types) in Board::line_dirs() {
+            if let (Some(pinned), Some(pinner)) = self.first_two_pieces(&king_pos, dir) {
+                let pinned_piece = at!(self, pinned).unwrap();
+                let pinner_piece = at!(self, pinner).unwrap();
+                if pinned_piece.color == color
+                    && pinner_piece.color != color
+                    && types.contains(&pinner_piece.piece_type)
+                {
+                    pins.push((pinned, pinner));
+                }
+            }
+        }
+        pins
+    }
+
+    /*
+     * Every line piece of color standing behind one of its own pieces that in
+     * turn stands in front of an opponent's piece. Moving the blocker uncovers an
+     * attack on the target, which is a discovered check when the target is the king.
+     */
+    pub fn discovered_attackers(&self, color: Color) -> Vec<DiscoveredAttack> {
+        let mut attacks = Vec::new();
+
+        for row in 0..BOARD_ROW_COUNT {
+            for col in 0..BOARD_COL_COUNT {
+                let attacker = Position {
+                    row: row as i8,
+                    col: col as i8,
+                };
+                let piece = match at!(self, attacker) {
+                    Some(piece) if piece.color == color => piece,
+                    _ => continue,
+                };
+
+                for (dir, types) in Board::line_dirs() {
+                    if !types.contains(&piece.piece_type) {
+                        continue;
+                    }
+                    if let (Some(blocker), Some(target)) = self.first_two_pieces(&attacker, dir) {
+                        if at!(self, blocker).unwrap().color == color
+                            && at!(self, target).unwrap().color != color
+                        {
+                            attacks.push(DiscoveredAttack {
+                                attacker,
+                                blocker,
+                                target,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        attacks
+    }
+
+    // All eight directions together with the piece types that move along them
+    pub(crate) fn line_dirs() -> Vec<([i8; 2], [PieceType; 2])> {
+        let adjacent = ADJACENT_DIRS
+            .iter()
+            .map(|dir| (*dir, [PieceType::Rook, PieceType::Queen]));
+        let diagonal = DIAGONAL_DIRS
+            .iter()
+            .map(|dir| (*dir, [PieceType::Bishop, PieceType::Queen]));
+        adjacent.chain(diagonal).collect()
+    }
+
+    // The first and second occupied squares seen from `from` in the direction dir
+    pub(crate) fn first_two_pieces(
+        &self,
+        from: &Position,
+        dir: [i8; 2],
+    ) -> (Option<Position>, Option<Position>) {
+        let first = self.first_piece(from, dir);
+        let second = first.and_then(|first| self.first_piece(&first, dir));
+        (first, second)
+    }
+
+    fn first_piece(&self, from: &Position, dir: [i8; 2]) -> Option<Position> {
+        self.generate_moves_line(from, &vec![dir])
+            .last()
+            .map(|mv| mv.to)
+            .filter(|to| at!(self, to).is_some())
+    }
+}
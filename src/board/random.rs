@@ -0,0 +1,74 @@
+use board::*;
+use piece::PieceType;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use square::Square;
+
+// How many placements to try before giving up on material that hardly ever gives a legal position
+const PLACEMENT_ATTEMPTS: usize = 10_000;
+
+impl Board {
+    // One of the legal moves, each as likely as the others, None if there are none
+    pub fn random_legal_move<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Move> {
+        self.generate_legal_moves().choose(rng).copied()
+    }
+
+    /*
+     * A legal position with a king for each color and the other pieces given,
+     * scattered over the board with either color to move. Pawns stay off the
+     * first and last ranks, and there is no castling or en passant.
+     */
+    pub fn random_position<R: Rng + ?Sized>(
+        rng: &mut R,
+        white: &[PieceType],
+        black: &[PieceType],
+    ) -> Result<Board, BoardError> {
+        if white.contains(&PieceType::King) || black.contains(&PieceType::King) {
+            return Err(BoardError::InvalidKingCount);
+        }
+        let pawns = white
+            .iter()
+            .chain(black.iter())
+            .filter(|&&piece_type| piece_type == PieceType::Pawn)
+            .count();
+        if white.len() + black.len() > 62 || pawns > 48 {
+            return Err(BoardError::InvalidMaterial);
+        }
+
+        let mut pieces: Vec<Piece> = Vec::new();
+        for (color, others) in [(Color::White, white), (Color::Black, black)] {
+            pieces.push(Piece {
+                piece_type: PieceType::King,
+                color,
+            });
+            pieces.extend(others.iter().map(|&piece_type| Piece { piece_type, color }));
+        }
+        // Pawns first, while there is still room for them
+        pieces.sort_by_key(|piece| piece.piece_type != PieceType::Pawn);
+
+        let mut error = BoardError::OpponentInCheck;
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            let mut squares = Square::ALL.to_vec();
+            squares.shuffle(rng);
+            let turn = match rng.gen() {
+                true => Color::White,
+                false => Color::Black,
+            };
+            let mut builder = BoardBuilder::new().turn(turn);
+            for &piece in pieces.iter() {
+                let index = squares
+                    .iter()
+                    .position(|square| {
+                        piece.piece_type != PieceType::Pawn || (1..=6).contains(&square.rank())
+                    })
+                    .unwrap();
+                builder = builder.piece(squares.remove(index), piece);
+            }
+            match builder.build() {
+                Ok(board) => return Ok(board),
+                Err(err) => error = err,
+            }
+        }
+        Err(error)
+    }
+}
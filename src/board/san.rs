@@ -0,0 +1,180 @@
+use board::*;
+use piece::PieceType;
+use square::Square;
+
+impl Board {
+    /*
+     * Writes a legal move in Standard Algebraic Notation, e.g. "Nbd7", "exd6",
+     * "e8=Q+" or "O-O-O#". Doesn't check that the move is legal.
+     */
+    pub fn move_to_san(&self, mv: &Move) -> String {
+        let piece = match self.piece_at(mv.from) {
+            Some(piece) => piece,
+            None => return mv.to_uci(),
+        };
+        let to = match mv.to.to_square() {
+            Some(to) => to,
+            None => return mv.to_uci(),
+        };
+
+        let mut san = String::new();
+        let dcol = mv.to.col - mv.from.col;
+        if piece.piece_type == PieceType::King && dcol.abs() == 2 {
+            san.push_str(match dcol > 0 {
+                true => "O-O",
+                false => "O-O-O",
+            });
+        } else {
+            let capture =
+                at!(self, mv.to).is_some() || (piece.piece_type == PieceType::Pawn && dcol != 0);
+
+            if piece.piece_type == PieceType::Pawn {
+                if capture {
+                    san.push((b'a' + mv.from.col as u8) as char);
+                }
+            } else {
+                san.push(piece.piece_type.to_char());
+                san.push_str(&self.san_disambiguation(mv, piece.piece_type));
+            }
+            if capture {
+                san.push('x');
+            }
+            san.push_str(&to.to_string());
+            if let Some(promotion) = mv.promotion {
+                san.push('=');
+                san.push(promotion.to_char());
+            }
+        }
+
+        let mut after = *self;
+        if after.unsafe_make_move(mv).is_ok() && after.is_in_check() {
+            san.push(match after.has_legal_moves() {
+                true => '+',
+                false => '#',
+            });
+        }
+        san
+    }
+
+    // The file, rank or square needed to tell mv apart from moves by other pieces of the same type
+    fn san_disambiguation(&self, mv: &Move, piece_type: PieceType) -> String {
+        let others: Vec<Move> = self
+            .legal_moves_to(mv.to)
+            .into_iter()
+            .filter(|other| {
+                other.from != mv.from
+                    && at!(self, other.from).map(|piece| piece.piece_type) == Some(piece_type)
+            })
+            .collect();
+        let from = mv.from.to_square().unwrap().to_string();
+
+        if others.is_empty() {
+            String::new()
+        } else if others.iter().all(|other| other.from.col != mv.from.col) {
+            from[0..1].to_string()
+        } else if others.iter().all(|other| other.from.row != mv.from.row) {
+            from[1..2].to_string()
+        } else {
+            from
+        }
+    }
+
+    /*
+     * Finds the legal move described by a move in Standard Algebraic Notation.
+     * Check and annotation symbols are ignored, and castling may be written
+     * with zeros. Returns IllegalMove if no legal move matches and AmbiguousMove
+     * if more than one does.
+     */
+    pub fn parse_san(&self, san: &str) -> Result<Move, BoardError> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+        if !san.is_ascii() || san.is_empty() {
+            return Err(BoardError::InvalidMoveNotation);
+        }
+
+        let castling = match san {
+            "O-O" | "0-0" => Some(2),
+            "O-O-O" | "0-0-0" => Some(-2),
+            _ => None,
+        };
+        if let Some(dcol) = castling {
+            let from = self.king_pos(self.turn);
+            let mv = Move::new(from, from + Position { row: 0, col: dcol }, None);
+            return match self.is_legal(&mv) {
+                true => Ok(mv),
+                false => Err(BoardError::IllegalMove),
+            };
+        }
+
+        // Split off the promotion, written either as "e8=Q" or "e8Q"
+        let (san, promotion) = match san.find('=') {
+            Some(i) => (&san[..i], Some(&san[i + 1..])),
+            None => match san.chars().last().filter(|c| c.is_ascii_uppercase()) {
+                Some(_) => (&san[..san.len() - 1], Some(&san[san.len() - 1..])),
+                None => (san, None),
+            },
+        };
+        let promotion = match promotion {
+            Some(promotion) => {
+                let mut chars = promotion.chars();
+                match (chars.next().and_then(PieceType::from_char), chars.next()) {
+                    (Some(promotion), None) => Some(promotion),
+                    _ => return Err(BoardError::InvalidMoveNotation),
+                }
+            }
+            None => None,
+        };
+
+        if san.len() < 2 {
+            return Err(BoardError::InvalidMoveNotation);
+        }
+        let to =
+            Square::from_algebraic(&san[san.len() - 2..]).ok_or(BoardError::InvalidMoveNotation)?;
+
+        let mut rest = &san[..san.len() - 2];
+        let piece_type = match rest.chars().next().filter(|c| c.is_ascii_uppercase()) {
+            Some(c) => {
+                rest = &rest[1..];
+                PieceType::from_char(c).ok_or(BoardError::InvalidMoveNotation)?
+            }
+            None => PieceType::Pawn,
+        };
+
+        // Whatever is left is the disambiguation and the capture sign
+        let mut from_col = None;
+        let mut from_row = None;
+        for c in rest.chars() {
+            match c {
+                'a'..='h' => from_col = Some(c as i8 - 'a' as i8),
+                '1'..='8' => from_row = Some(BOARD_ROW_COUNT as i8 - 1 - (c as i8 - '1' as i8)),
+                'x' | ':' | '-' => {}
+                _ => return Err(BoardError::InvalidMoveNotation),
+            }
+        }
+
+        let candidates: Vec<Move> = self
+            .legal_moves_to(to)
+            .into_iter()
+            .filter(|mv| {
+                at!(self, mv.from).map(|piece| piece.piece_type) == Some(piece_type)
+                    && mv.promotion == promotion
+                    && from_col.is_none_or(|col| mv.from.col == col)
+                    && from_row.is_none_or(|row| mv.from.row == row)
+            })
+            .collect();
+
+        match candidates.len() {
+            0 => Err(BoardError::IllegalMove),
+            1 => Ok(candidates[0]),
+            _ => Err(BoardError::AmbiguousMove),
+        }
+    }
+
+    // Accepts a legal move in UCI ("g1f3") or SAN ("Nf3"), IllegalMove for any other move
+    pub fn parse_move(&self, notation: &str) -> Result<Move, BoardError> {
+        match Move::from_uci(notation) {
+            Ok(mv) if self.is_legal(&mv) => Ok(mv),
+            Ok(_) => Err(BoardError::IllegalMove),
+            Err(_) => self.parse_san(notation),
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use board::*;
+use piece::PieceType;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GameStatus {
+    Ongoing,
+    // The player whose turn it is is in check but has legal moves
+    Check,
+    // The player that made the last move won
+    Checkmate,
+    Stalemate,
+    // 75 moves by each player without a capture or a pawn move, after 50 a player may claim the draw
+    SeventyFiveMoveRule,
+    // Neither player has the pieces needed to checkmate
+    InsufficientMaterial,
+    // Only reported by Game::status(), since a Board doesn't know the earlier positions
+    ThreefoldRepetition,
+}
+
+impl GameStatus {
+    #[inline]
+    pub fn is_game_over(self) -> bool {
+        !matches!(self, GameStatus::Ongoing | GameStatus::Check)
+    }
+
+    #[inline]
+    pub fn is_draw(self) -> bool {
+        matches!(
+            self,
+            GameStatus::Stalemate
+                | GameStatus::SeventyFiveMoveRule
+                | GameStatus::InsufficientMaterial
+                | GameStatus::ThreefoldRepetition
+        )
+    }
+}
+
+// Number of half moves without captures or pawn moves that make up 50 moves
+pub const FIFTY_MOVE_RULE_HALFMOVES: u32 = 100;
+// After 75 moves the game is drawn even if nobody claims it
+pub const SEVENTY_FIVE_MOVE_RULE_HALFMOVES: u32 = 150;
+
+impl Board {
+    // Generates the legal moves only once, use status_and_moves() if you need them as well
+    #[inline]
+    pub fn status(&self) -> GameStatus {
+        self.status_and_moves().0
+    }
+
+    pub fn status_and_moves(&self) -> (GameStatus, Vec<Move>) {
+        let moves = self.generate_legal_moves();
+        let in_check = self.is_in_check();
+
+        let status = if moves.is_empty() {
+            match in_check {
+                true => GameStatus::Checkmate,
+                false => GameStatus::Stalemate,
+            }
+        } else if self.halfmove_clock >= SEVENTY_FIVE_MOVE_RULE_HALFMOVES {
+            GameStatus::SeventyFiveMoveRule
+        } else if self.is_insufficient_material() {
+            GameStatus::InsufficientMaterial
+        } else if in_check {
+            GameStatus::Check
+        } else {
+            GameStatus::Ongoing
+        };
+        (status, moves)
+    }
+
+    // Whether the king of the player whose turn it is is attacked, no matter if it can escape
+    #[inline]
+    pub fn is_in_check(&self) -> bool {
+        self.is_attacked_by_opponent(&self.king_pos(self.turn))
+    }
+
+    // Half moves since the last capture or pawn move
+    #[inline]
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    // Whether the player to move may claim a draw under the fifty move rule, see Game::claim_draw()
+    pub fn can_claim_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= FIFTY_MOVE_RULE_HALFMOVES && !self.status().is_game_over()
+    }
+
+    #[inline]
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    /*
+     * True if no sequence of moves can lead to checkmate, which is the case with
+     * only kings left, a single knight or bishop, or bishops that all share a square color
+     */
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut minor_pieces = 0;
+        let mut knights = 0;
+        let mut bishop_square_colors = [false; 2];
+
+        for row in 0..BOARD_ROW_COUNT {
+            for col in 0..BOARD_COL_COUNT {
+                match self.board[row][col].map(|piece| piece.piece_type) {
+                    Some(PieceType::Pawn) | Some(PieceType::Rook) | Some(PieceType::Queen) => {
+                        return false
+                    }
+                    Some(PieceType::Knight) => {
+                        minor_pieces += 1;
+                        knights += 1;
+                    }
+                    Some(PieceType::Bishop) => {
+                        minor_pieces += 1;
+                        bishop_square_colors[(row + col) % 2] = true;
+                    }
+                    Some(PieceType::King) | None => {}
+                }
+            }
+        }
+
+        minor_pieces <= 1 || (knights == 0 && !(bishop_square_colors[0] && bishop_square_colors[1]))
+    }
+
+    /*
+     * Whether color has the pieces to checkmate with the help of the opponent, which
+     * decides if running out of time loses or draws. A lone knight needs an opponent
+     * piece to hem in the king, bishops on one square color need an opponent piece that
+     * can stand on the other color.
+     */
+    pub fn can_checkmate(&self, color: Color) -> bool {
+        let mut knights = 0;
+        let mut bishop_square_colors = [false; 2];
+        let mut opponent_pieces = 0;
+        // Opponent pieces that can block on each square color, a bishop only on its own
+        let mut opponent_blockers = [false; 2];
+
+        for row in 0..BOARD_ROW_COUNT {
+            for col in 0..BOARD_COL_COUNT {
+                let piece = match self.board[row][col] {
+                    Some(piece) => piece,
+                    None => continue,
+                };
+                if piece.color != color {
+                    match piece.piece_type {
+                        PieceType::King => {}
+                        PieceType::Bishop => {
+                            opponent_pieces += 1;
+                            opponent_blockers[(row + col) % 2] = true;
+                        }
+                        PieceType::Pawn
+                        | PieceType::Knight
+                        | PieceType::Rook
+                        | PieceType::Queen => {
+                            opponent_pieces += 1;
+                            opponent_blockers = [true; 2];
+                        }
+                    }
+                    continue;
+                }
+                match piece.piece_type {
+                    PieceType::Pawn | PieceType::Rook | PieceType::Queen => return true,
+                    PieceType::Knight => knights += 1,
+                    PieceType::Bishop => bishop_square_colors[(row + col) % 2] = true,
+                    PieceType::King => {}
+                }
+            }
+        }
+
+        match (knights, bishop_square_colors) {
+            (0, [false, false]) => false,
+            (0, [true, true]) => true,
+            // The king gets mated on the bishops' color, so the escape squares have the other one
+            (0, [light, _]) => opponent_blockers[light as usize],
+            (1, [false, false]) => opponent_pieces > 0,
+            _ => true,
+        }
+    }
+
+    // Same pieces, player to move, castling rights and en passant, ignoring the move counters
+    pub(crate) fn is_same_position(&self, other: &Board) -> bool {
+        self.board == other.board
+            && self.turn == other.turn
+            && self.short_castle_white == other.short_castle_white
+            && self.long_castle_white == other.long_castle_white
+            && self.short_castle_black == other.short_castle_black
+            && self.long_castle_black == other.long_castle_black
+            && self.en_passant_col == other.en_passant_col
+    }
+}
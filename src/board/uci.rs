@@ -0,0 +1,56 @@
+use board::*;
+use piece::PieceType;
+use square::Square;
+use std::fmt;
+use std::str::FromStr;
+
+impl Move {
+    /*
+     * Parses moves in the long algebraic notation used by UCI, e.g. "e2e4" or
+     * "e7e8q". Castling is written as the king's move, e.g. "e1g1".
+     * Doesn't check whether the move is legal.
+     */
+    pub fn from_uci(uci: &str) -> Result<Move, BoardError> {
+        if !uci.is_ascii() || (uci.len() != 4 && uci.len() != 5) {
+            return Err(BoardError::InvalidMoveNotation);
+        }
+        let from = Square::from_algebraic(&uci[0..2]).ok_or(BoardError::InvalidMoveNotation)?;
+        let to = Square::from_algebraic(&uci[2..4]).ok_or(BoardError::InvalidMoveNotation)?;
+        let promotion = match uci[4..].chars().next() {
+            Some(c) => match PieceType::from_char(c) {
+                Some(PieceType::Pawn) | Some(PieceType::King) | None => {
+                    return Err(BoardError::InvalidMoveNotation)
+                }
+                promotion => promotion,
+            },
+            None => None,
+        };
+        Ok(Move::new(from, to, promotion))
+    }
+
+    // Moves that are outside of the board are written as the null move "0000"
+    pub fn to_uci(&self) -> String {
+        match (self.from.to_square(), self.to.to_square()) {
+            (Some(from), Some(to)) => match self.promotion {
+                Some(promotion) => {
+                    format!("{}{}{}", from, to, promotion.to_char().to_ascii_lowercase())
+                }
+                None => format!("{}{}", from, to),
+            },
+            _ => "0000".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_uci())
+    }
+}
+
+impl FromStr for Move {
+    type Err = BoardError;
+    fn from_str(uci: &str) -> Result<Self, Self::Err> {
+        Move::from_uci(uci)
+    }
+}
@@ -0,0 +1,130 @@
+use board::{Board, Move};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use std::convert::TryInto;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+mod builder;
+pub use self::builder::*;
+
+// Size of an entry in a Polyglot .bin file
+const ENTRY_SIZE: usize = 16;
+
+// A move of an opening book together with how often it should be played
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BookEntry {
+    pub mv: Move,
+    pub weight: u16,
+    // Extra data some tools store in Polyglot books, usually 0
+    pub learn: u32,
+}
+
+// An entry as it is stored in the file, the move can only be decoded with the board
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct RawEntry {
+    key: u64,
+    mv: u16,
+    weight: u16,
+    learn: u32,
+}
+
+/*
+ * An opening book in the Polyglot .bin format. The entries are looked up
+ * by Board::polyglot_key() with a binary search.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Book {
+    // Sorted by key, entries with the same key keep the order of the file
+    entries: Vec<RawEntry>,
+}
+
+impl Book {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Book> {
+        Book::from_bytes(&fs::read(path)?)
+    }
+
+    // Fails with InvalidData if bytes isn't made of whole 16 byte entries
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Book> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "polyglot books consist of 16 byte entries",
+            ));
+        }
+        let mut entries: Vec<RawEntry> = bytes
+            .chunks(ENTRY_SIZE)
+            .map(|entry| RawEntry {
+                key: u64::from_be_bytes(entry[0..8].try_into().unwrap()),
+                mv: u16::from_be_bytes(entry[8..10].try_into().unwrap()),
+                weight: u16::from_be_bytes(entry[10..12].try_into().unwrap()),
+                learn: u32::from_be_bytes(entry[12..16].try_into().unwrap()),
+            })
+            .collect();
+        // Books are supposed to be sorted already, but not every tool does it
+        entries.sort_by_key(|entry| entry.key);
+        Ok(Book { entries })
+    }
+
+    // The book in the Polyglot format, ready to be written to a .bin file
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.entries.len() * ENTRY_SIZE);
+        for entry in self.entries.iter() {
+            bytes.extend_from_slice(&entry.key.to_be_bytes());
+            bytes.extend_from_slice(&entry.mv.to_be_bytes());
+            bytes.extend_from_slice(&entry.weight.to_be_bytes());
+            bytes.extend_from_slice(&entry.learn.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // The legal book moves for the position, in the order of the file
+    pub fn entries(&self, board: &Board) -> Vec<BookEntry> {
+        let key = board.polyglot_key();
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        self.entries[start..]
+            .iter()
+            .take_while(|entry| entry.key == key)
+            .map(|entry| BookEntry {
+                mv: board.move_from_polyglot(entry.mv),
+                weight: entry.weight,
+                learn: entry.learn,
+            })
+            .filter(|entry| board.is_legal(&entry.mv))
+            .collect()
+    }
+
+    // The move with the highest weight
+    pub fn best_move(&self, board: &Board) -> Option<Move> {
+        self.entries(board)
+            .iter()
+            .rev()
+            .max_by_key(|entry| entry.weight)
+            .map(|entry| entry.mv)
+    }
+
+    // Picks a book move at random, the chance of each move is proportional to its weight
+    pub fn choose_move<R: Rng + ?Sized>(&self, board: &Board, rng: &mut R) -> Option<Move> {
+        let entries = self.entries(board);
+        if entries.is_empty() {
+            return None;
+        }
+        let index = match WeightedIndex::new(entries.iter().map(|entry| entry.weight)) {
+            Ok(weights) => weights.sample(rng),
+            // Every weight is 0, so treat the moves equally
+            Err(_) => rng.gen_range(0..entries.len()),
+        };
+        Some(entries[index].mv)
+    }
+}
@@ -0,0 +1,228 @@
+use board::{Board, Move};
+use book::{Book, RawEntry};
+use game::{Game, PgnReader};
+use piece::Color;
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+
+// How many half moves of each game are added to a book unless BookBuilder::max_ply() says otherwise
+pub const DEFAULT_MAX_PLY: usize = 24;
+
+// Start of files in the native format, which is followed by NATIVE_ENTRY_SIZE byte entries
+const NATIVE_MAGIC: &[u8; 8] = b"CHAPIBK1";
+const NATIVE_ENTRY_SIZE: usize = 26;
+
+// How a move did in the games of a book, from the point of view of the player who made it
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MoveStats {
+    pub mv: Move,
+    pub count: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct RawStats {
+    // The move as it is written in Polyglot books
+    mv: u16,
+    count: u32,
+    wins: u32,
+    draws: u32,
+    losses: u32,
+}
+
+impl RawStats {
+    // Wins count twice as much as draws, losses not at all
+    #[inline]
+    fn score(&self) -> u64 {
+        2 * self.wins as u64 + self.draws as u64
+    }
+}
+
+/*
+ * Collects the moves played in the first plies of many games to build an
+ * opening book. The statistics can be saved in the crate's own format, which
+ * keeps all of them, or as a Polyglot book that only keeps a weight per move.
+ */
+#[derive(Debug, Clone)]
+pub struct BookBuilder {
+    max_ply: usize,
+    // Polyglot key of the position to the moves played in it
+    positions: BTreeMap<u64, Vec<RawStats>>,
+}
+
+impl Default for BookBuilder {
+    fn default() -> Self {
+        BookBuilder {
+            max_ply: DEFAULT_MAX_PLY,
+            positions: BTreeMap::new(),
+        }
+    }
+}
+
+impl BookBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // Only the first max_ply half moves of the games added after this are used
+    pub fn max_ply(mut self, max_ply: usize) -> Self {
+        self.max_ply = max_ply;
+        self
+    }
+
+    // Number of positions with at least one move
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    // Games without a result, e.g. "*" in PGN, only add to the counts
+    pub fn add_game(&mut self, game: &Game) {
+        let winner = match game.result() {
+            Some(result) => Some(result.winner),
+            None => match game.tag("Result") {
+                Some("1-0") => Some(Some(Color::White)),
+                Some("0-1") => Some(Some(Color::Black)),
+                Some("1/2-1/2") => Some(None),
+                _ => None,
+            },
+        };
+
+        let mut board = *game.start_position();
+        for mv in game.moves().iter().take(self.max_ply) {
+            let raw = board.move_to_polyglot(mv);
+            let moves = self.positions.entry(board.polyglot_key()).or_default();
+            let index = match moves.iter().position(|stats| stats.mv == raw) {
+                Some(index) => index,
+                None => {
+                    moves.push(RawStats {
+                        mv: raw,
+                        count: 0,
+                        wins: 0,
+                        draws: 0,
+                        losses: 0,
+                    });
+                    moves.len() - 1
+                }
+            };
+            let stats = &mut moves[index];
+            stats.count += 1;
+            match winner {
+                Some(Some(color)) if color == board.whose_turn() => stats.wins += 1,
+                Some(Some(_)) => stats.losses += 1,
+                Some(None) => stats.draws += 1,
+                None => {}
+            }
+            board.make_move(mv).unwrap();
+        }
+    }
+
+    // Adds every game of a PGN file and returns how many there were, skipping games that can't be read
+    pub fn add_pgn(&mut self, pgn: &str) -> usize {
+        let mut count = 0;
+        for game in PgnReader::new(pgn).flatten() {
+            self.add_game(&game);
+            count += 1;
+        }
+        count
+    }
+
+    // The moves played in the position, most played first
+    pub fn stats(&self, board: &Board) -> Vec<MoveStats> {
+        let mut stats: Vec<MoveStats> = self
+            .positions
+            .get(&board.polyglot_key())
+            .map_or(&[][..], |moves| &moves[..])
+            .iter()
+            .map(|stats| MoveStats {
+                mv: board.move_from_polyglot(stats.mv),
+                count: stats.count,
+                wins: stats.wins,
+                draws: stats.draws,
+                losses: stats.losses,
+            })
+            .collect();
+        stats.sort_by_key(|stats| std::cmp::Reverse(stats.count));
+        stats
+    }
+
+    /*
+     * A Polyglot book where the weight of a move is two points per win and one per
+     * draw. The weights of a position are scaled down together if they don't fit.
+     */
+    pub fn build(&self) -> Book {
+        let mut entries = Vec::new();
+        for (&key, moves) in self.positions.iter() {
+            let max_score = moves.iter().map(RawStats::score).max().unwrap_or(0);
+            let scale = (max_score / u16::MAX as u64) + 1;
+            let mut position_entries: Vec<RawEntry> = moves
+                .iter()
+                .map(|stats| RawEntry {
+                    key,
+                    mv: stats.mv,
+                    weight: (stats.score() / scale) as u16,
+                    learn: 0,
+                })
+                .collect();
+            position_entries.sort_by_key(|entry| std::cmp::Reverse(entry.weight));
+            entries.extend(position_entries);
+        }
+        Book { entries }
+    }
+
+    pub fn write_polyglot<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&self.build().to_bytes())
+    }
+
+    // Writes all of the statistics in the crate's own format, see read_native()
+    pub fn write_native<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut bytes = NATIVE_MAGIC.to_vec();
+        for (&key, moves) in self.positions.iter() {
+            for stats in moves {
+                bytes.extend_from_slice(&key.to_be_bytes());
+                bytes.extend_from_slice(&stats.mv.to_be_bytes());
+                for value in [stats.count, stats.wins, stats.draws, stats.losses] {
+                    bytes.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+        }
+        writer.write_all(&bytes)
+    }
+
+    // Reads statistics written by write_native(), more games can be added to them afterwards
+    pub fn read_native<R: Read>(mut reader: R) -> io::Result<BookBuilder> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        if !bytes.starts_with(NATIVE_MAGIC)
+            || !(bytes.len() - NATIVE_MAGIC.len()).is_multiple_of(NATIVE_ENTRY_SIZE)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a book in the native format",
+            ));
+        }
+
+        let mut builder = BookBuilder::new();
+        for entry in bytes[NATIVE_MAGIC.len()..].chunks(NATIVE_ENTRY_SIZE) {
+            let value = |i: usize| u32::from_be_bytes(entry[i..i + 4].try_into().unwrap());
+            let key = u64::from_be_bytes(entry[0..8].try_into().unwrap());
+            builder.positions.entry(key).or_default().push(RawStats {
+                mv: u16::from_be_bytes(entry[8..10].try_into().unwrap()),
+                count: value(10),
+                wins: value(14),
+                draws: value(18),
+                losses: value(22),
+            });
+        }
+        Ok(builder)
+    }
+}
@@ -0,0 +1,430 @@
+use board::{BoardError, Move};
+use game::{Game, GameResult};
+use piece::Color;
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+// Where a Clock gets the current time from, so tests can replace the real time
+pub trait TimeSource {
+    // Time since some fixed moment, it must never go backwards
+    fn now(&self) -> Duration;
+}
+
+impl<T: TimeSource + ?Sized> TimeSource for &T {
+    #[inline]
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+}
+
+// The time measured by the operating system
+#[derive(Debug, Copy, Clone)]
+pub struct RealTime {
+    start: Instant,
+}
+
+impl Default for RealTime {
+    fn default() -> Self {
+        RealTime {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl TimeSource for RealTime {
+    #[inline]
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+// Time that only passes when told to, for tests and replaying games
+#[derive(Debug, Default)]
+pub struct ManualTime {
+    now: Cell<Duration>,
+}
+
+impl ManualTime {
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    #[inline]
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl TimeSource for ManualTime {
+    #[inline]
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+// Extra time a player gets for each move
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Increment {
+    None,
+    // Added after every move
+    Fischer(Duration),
+    // The time used for the move is given back after it, up to this much
+    Bronstein(Duration),
+    // The clock only starts counting down after this much of each move (simple or US delay)
+    Delay(Duration),
+}
+
+// A part of a time control, e.g. 40 moves in 90 minutes
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Period {
+    // None for the rest of the game
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub increment: Increment,
+}
+
+impl Period {
+    // A period lasting for the rest of the game without an increment
+    pub fn new(time: Duration) -> Self {
+        Period {
+            moves: None,
+            time,
+            increment: Increment::None,
+        }
+    }
+
+    pub fn moves(mut self, moves: u32) -> Self {
+        self.moves = Some(moves);
+        self
+    }
+
+    pub fn increment(mut self, increment: Increment) -> Self {
+        self.increment = increment;
+        self
+    }
+}
+
+/*
+ * The periods a game is played in. Once a player has made the moves of a period
+ * the time of the next one is added to the clock. If the last period has a number
+ * of moves it is repeated, so 40/2h on its own gives 2 hours for every 40 moves.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TimeControl {
+    periods: Vec<Period>,
+}
+
+impl TimeControl {
+    // Panics if there are no periods
+    pub fn new(periods: Vec<Period>) -> Self {
+        assert!(
+            !periods.is_empty(),
+            "a time control needs at least one period"
+        );
+        TimeControl { periods }
+    }
+
+    pub fn sudden_death(time: Duration) -> Self {
+        TimeControl::new(vec![Period::new(time)])
+    }
+
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        TimeControl::new(vec![
+            Period::new(time).increment(Increment::Fischer(increment))
+        ])
+    }
+
+    pub fn bronstein(time: Duration, delay: Duration) -> Self {
+        TimeControl::new(vec![
+            Period::new(time).increment(Increment::Bronstein(delay))
+        ])
+    }
+
+    pub fn simple_delay(time: Duration, delay: Duration) -> Self {
+        TimeControl::new(vec![Period::new(time).increment(Increment::Delay(delay))])
+    }
+
+    #[inline]
+    pub fn periods(&self) -> &[Period] {
+        &self.periods
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct PlayerClock {
+    remaining: Duration,
+    period: usize,
+    moves_in_period: u32,
+}
+
+/*
+ * A chess clock for two players. The clock of the player to move runs between
+ * calls to press(), and a player has lost on time (flagged) as soon as their
+ * remaining time reaches zero.
+ */
+#[derive(Debug, Clone)]
+pub struct Clock<T: TimeSource = RealTime> {
+    control: TimeControl,
+    time_source: T,
+    players: [PlayerClock; 2],
+    turn: Color,
+    // When the current turn started or was resumed, None while the clock is stopped
+    running_since: Option<Duration>,
+    // Time used in the current turn before the clock was last stopped
+    used: Duration,
+    flagged: Option<Color>,
+}
+
+#[inline]
+fn index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+impl Clock<RealTime> {
+    pub fn new(control: TimeControl) -> Self {
+        Clock::with_time_source(control, RealTime::default())
+    }
+}
+
+impl<T: TimeSource> Clock<T> {
+    // The clock starts stopped, with White to move
+    pub fn with_time_source(control: TimeControl, time_source: T) -> Self {
+        let player = PlayerClock {
+            remaining: control.periods[0].time,
+            period: 0,
+            moves_in_period: 0,
+        };
+        Clock {
+            control,
+            time_source,
+            players: [player; 2],
+            turn: Color::White,
+            running_since: None,
+            used: Duration::ZERO,
+            flagged: None,
+        }
+    }
+
+    #[inline]
+    pub fn time_control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    #[inline]
+    pub fn time_source(&self) -> &T {
+        &self.time_source
+    }
+
+    #[inline]
+    pub fn whose_turn(&self) -> Color {
+        self.turn
+    }
+
+    #[inline]
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    /*
+     * Starts or resumes the clock of color, continuing the turn if it was already
+     * theirs. Otherwise the time used in the interrupted turn comes off the clock
+     * of the other player, without an increment, and may flag them.
+     */
+    pub fn start(&mut self, color: Color) {
+        if self.flagged.is_some() {
+            return;
+        }
+        if color != self.turn {
+            self.stop();
+            let charged = self.charged(self.turn, self.used);
+            let player = &mut self.players[index(self.turn)];
+            player.remaining = player.remaining.saturating_sub(charged);
+            if player.remaining.is_zero() {
+                self.flagged = Some(self.turn);
+                return;
+            }
+            self.turn = color;
+            self.used = Duration::ZERO;
+        }
+        if self.running_since.is_none() {
+            self.running_since = Some(self.time_source.now());
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            self.used += self.time_source.now().saturating_sub(since);
+        }
+    }
+
+    // Time used in the current turn so far
+    fn turn_time(&self) -> Duration {
+        self.used
+            + self.running_since.map_or(Duration::ZERO, |since| {
+                self.time_source.now().saturating_sub(since)
+            })
+    }
+
+    // The part of the turn time that comes off the clock
+    fn charged(&self, color: Color, turn_time: Duration) -> Duration {
+        let player = &self.players[index(color)];
+        match self.period(player).increment {
+            Increment::Delay(delay) => turn_time.saturating_sub(delay),
+            _ => turn_time,
+        }
+    }
+
+    fn period(&self, player: &PlayerClock) -> &Period {
+        &self.control.periods[player.period]
+    }
+
+    // Time left on the clock of color, counting the current turn
+    pub fn remaining(&self, color: Color) -> Duration {
+        let remaining = self.players[index(color)].remaining;
+        if color != self.turn || self.flagged.is_some() {
+            return remaining;
+        }
+        remaining.saturating_sub(self.charged(color, self.turn_time()))
+    }
+
+    // The player who ran out of time, if any
+    pub fn flagged(&self) -> Option<Color> {
+        if self.flagged.is_none() && self.remaining(self.turn).is_zero() {
+            return Some(self.turn);
+        }
+        self.flagged
+    }
+
+    /*
+     * Ends the turn of the player to move and starts the clock of the opponent.
+     * Returns false without doing anything if the player has already run out of time.
+     */
+    pub fn press(&mut self) -> bool {
+        // The time is read once, a later reading could charge more than is left
+        let turn_time = self.turn_time();
+        let charged = self.charged(self.turn, turn_time);
+        let remaining = self.players[index(self.turn)]
+            .remaining
+            .saturating_sub(charged);
+        if self.flagged.is_some() || remaining.is_zero() {
+            let color = self.flagged.unwrap_or(self.turn);
+            self.stop();
+            self.players[index(color)].remaining = Duration::ZERO;
+            self.flagged = Some(color);
+            return false;
+        }
+
+        let control = &self.control;
+        let player = &mut self.players[index(self.turn)];
+        let period = control.periods[player.period];
+
+        player.remaining = remaining;
+        match period.increment {
+            Increment::Fischer(increment) => player.remaining += increment,
+            Increment::Bronstein(delay) => player.remaining += turn_time.min(delay),
+            Increment::None | Increment::Delay(_) => {}
+        }
+
+        player.moves_in_period += 1;
+        if period.moves == Some(player.moves_in_period) {
+            // The last period repeats
+            player.period = (player.period + 1).min(control.periods.len() - 1);
+            player.moves_in_period = 0;
+            player.remaining += control.periods[player.period].time;
+        }
+
+        let running = self.is_running();
+        self.stop();
+        self.turn = self.turn.flip();
+        self.used = Duration::ZERO;
+        if running {
+            self.start(self.turn);
+        }
+        true
+    }
+}
+
+/*
+ * A Game played on a Clock. The clock starts for the player to move right away
+ * and is pressed after every move, and a player who runs out of time loses,
+ * unless the opponent couldn't checkmate them anymore, which makes it a draw.
+ */
+#[derive(Debug, Clone)]
+pub struct TimedGame<T: TimeSource = RealTime> {
+    game: Game,
+    clock: Clock<T>,
+}
+
+impl<T: TimeSource> TimedGame<T> {
+    pub fn new(game: Game, mut clock: Clock<T>) -> Self {
+        if !game.is_over() {
+            clock.start(game.board().whose_turn());
+        }
+        TimedGame { game, clock }
+    }
+
+    #[inline]
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    #[inline]
+    pub fn clock(&self) -> &Clock<T> {
+        &self.clock
+    }
+
+    // Ends the game if the player to move has run out of time, call it regularly
+    pub fn check_time(&mut self) -> Option<GameResult> {
+        if !self.game.is_over() {
+            if let Some(color) = self.clock.flagged() {
+                self.clock.press();
+                self.game.time_forfeit(color);
+            }
+        }
+        self.game.result()
+    }
+
+    // Like Game::make_move(), but a move made after the time ran out ends the game instead
+    pub fn make_move(&mut self, mv: &Move) -> Result<(), BoardError> {
+        if self.check_time().is_some() {
+            return Err(BoardError::GameOver);
+        }
+        // An illegal move doesn't end the turn, and the flag may fall while pressing
+        let color = self.game.board().whose_turn();
+        if !self.game.board().is_legal(mv) {
+            return Err(BoardError::IllegalMove);
+        }
+        if !self.clock.press() {
+            self.game.time_forfeit(color);
+            return Err(BoardError::GameOver);
+        }
+        self.game.make_move(mv)?;
+        if self.game.is_over() {
+            self.clock.stop();
+        }
+        Ok(())
+    }
+
+    pub fn resign(&mut self, color: Color) {
+        if self.check_time().is_none() {
+            self.game.resign(color);
+            self.clock.stop();
+        }
+    }
+
+    pub fn agree_draw(&mut self) {
+        if self.check_time().is_none() {
+            self.game.agree_draw();
+            self.clock.stop();
+        }
+    }
+
+    #[inline]
+    pub fn into_game(self) -> Game {
+        self.game
+    }
+}
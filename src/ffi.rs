@@ -0,0 +1,362 @@
+/*
+ * A C interface to the rules, declared in include/chessapi.h. A ChessBoard is
+ * a Game behind a pointer, so moves can be taken back and repetitions count.
+ * Squares are numbered from 0 for a1 to 63 for h8, like Square::index().
+ * The header is generated from this file with `cargo build --features header`,
+ * which is why the exported items have /// comments: cbindgen copies those.
+ */
+// The rules for the pointers are written in the doc comments, which end up in the header
+#![allow(clippy::missing_safety_doc)]
+
+use board::{Board, BoardError, GameStatus, Move};
+use game::Game;
+use piece::{Color, PieceType};
+use square::Square;
+use std::convert::TryFrom;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// Promotion of a ChessMove, the pieces a pawn can become
+pub const CHESS_PROMOTION_NONE: u8 = 0;
+pub const CHESS_PROMOTION_KNIGHT: u8 = 1;
+pub const CHESS_PROMOTION_BISHOP: u8 = 2;
+pub const CHESS_PROMOTION_ROOK: u8 = 3;
+pub const CHESS_PROMOTION_QUEEN: u8 = 4;
+
+/// A game from a position on, including the moves made since, so they can be taken back
+pub struct ChessBoard {
+    game: Game,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ChessMove {
+    pub from: u8,
+    pub to: u8,
+    pub promotion: u8,
+}
+
+/// CHESS_ERROR_OK, then one code for each BoardError in the same order
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChessError {
+    Ok = 0,
+    IllegalMove,
+    InvalidSquare,
+    InvalidKingCount,
+    PawnOnBackRank,
+    OpponentInCheck,
+    InvalidCastlingRights,
+    InvalidEnPassant,
+    InvalidFen,
+    InvalidMoveNotation,
+    AmbiguousMove,
+    GameOver,
+    InvalidPgn,
+    InvalidMaterial,
+    NullPointer = -1,
+    /// A bug in ChessAPI, the call panicked
+    Internal = -2,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChessStatus {
+    Ongoing,
+    Check,
+    /// The player that made the last move won
+    Checkmate,
+    Stalemate,
+    SeventyFiveMoveRule,
+    InsufficientMaterial,
+    ThreefoldRepetition,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChessColor {
+    White,
+    Black,
+}
+
+impl From<BoardError> for ChessError {
+    fn from(err: BoardError) -> Self {
+        match err {
+            BoardError::IllegalMove => ChessError::IllegalMove,
+            BoardError::InvalidSquare => ChessError::InvalidSquare,
+            BoardError::InvalidKingCount => ChessError::InvalidKingCount,
+            BoardError::PawnOnBackRank => ChessError::PawnOnBackRank,
+            BoardError::OpponentInCheck => ChessError::OpponentInCheck,
+            BoardError::InvalidCastlingRights => ChessError::InvalidCastlingRights,
+            BoardError::InvalidEnPassant => ChessError::InvalidEnPassant,
+            BoardError::InvalidFen => ChessError::InvalidFen,
+            BoardError::InvalidMoveNotation => ChessError::InvalidMoveNotation,
+            BoardError::AmbiguousMove => ChessError::AmbiguousMove,
+            BoardError::GameOver => ChessError::GameOver,
+            BoardError::InvalidPgn => ChessError::InvalidPgn,
+            BoardError::InvalidMaterial => ChessError::InvalidMaterial,
+        }
+    }
+}
+
+impl From<GameStatus> for ChessStatus {
+    fn from(status: GameStatus) -> Self {
+        match status {
+            GameStatus::Ongoing => ChessStatus::Ongoing,
+            GameStatus::Check => ChessStatus::Check,
+            GameStatus::Checkmate => ChessStatus::Checkmate,
+            GameStatus::Stalemate => ChessStatus::Stalemate,
+            GameStatus::SeventyFiveMoveRule => ChessStatus::SeventyFiveMoveRule,
+            GameStatus::InsufficientMaterial => ChessStatus::InsufficientMaterial,
+            GameStatus::ThreefoldRepetition => ChessStatus::ThreefoldRepetition,
+        }
+    }
+}
+
+impl From<Move> for ChessMove {
+    fn from(mv: Move) -> Self {
+        ChessMove {
+            from: Square::try_from(mv.from).unwrap().index() as u8,
+            to: Square::try_from(mv.to).unwrap().index() as u8,
+            promotion: match mv.promotion {
+                Some(PieceType::Knight) => CHESS_PROMOTION_KNIGHT,
+                Some(PieceType::Bishop) => CHESS_PROMOTION_BISHOP,
+                Some(PieceType::Rook) => CHESS_PROMOTION_ROOK,
+                Some(PieceType::Queen) => CHESS_PROMOTION_QUEEN,
+                _ => CHESS_PROMOTION_NONE,
+            },
+        }
+    }
+}
+
+impl TryFrom<ChessMove> for Move {
+    type Error = BoardError;
+
+    fn try_from(mv: ChessMove) -> Result<Self, Self::Error> {
+        let square = |index: u8| {
+            Square::ALL
+                .get(index as usize)
+                .copied()
+                .ok_or(BoardError::InvalidSquare)
+        };
+        let promotion = match mv.promotion {
+            CHESS_PROMOTION_NONE => None,
+            CHESS_PROMOTION_KNIGHT => Some(PieceType::Knight),
+            CHESS_PROMOTION_BISHOP => Some(PieceType::Bishop),
+            CHESS_PROMOTION_ROOK => Some(PieceType::Rook),
+            CHESS_PROMOTION_QUEEN => Some(PieceType::Queen),
+            _ => return Err(BoardError::IllegalMove),
+        };
+        Ok(Move::new(square(mv.from)?, square(mv.to)?, promotion))
+    }
+}
+
+// Copies text and a terminating zero into buffer as far as it fits, like snprintf()
+unsafe fn write_text(text: &str, buffer: *mut c_char, capacity: usize) -> usize {
+    if !buffer.is_null() && capacity > 0 {
+        let length = text.len().min(capacity - 1);
+        ptr::copy_nonoverlapping(text.as_ptr() as *const c_char, buffer, length);
+        *buffer.add(length) = 0;
+    }
+    text.len()
+}
+
+/*
+ * Runs an entry point and returns fallback if the rules code panics, since
+ * unwinding into C aborts the whole program. Functions that return a
+ * ChessError use ChessError::Internal.
+ */
+pub(crate) fn guard<R, F: FnOnce() -> R>(fallback: R, f: F) -> R {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+unsafe fn read_text<'a>(text: *const c_char) -> Option<&'a str> {
+    match text.is_null() {
+        true => None,
+        false => CStr::from_ptr(text).to_str().ok(),
+    }
+}
+
+/// A board with the starting position, free it with chess_board_free()
+#[no_mangle]
+pub extern "C" fn chess_board_new() -> *mut ChessBoard {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::new(ChessBoard { game: Game::new() }))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_free(board: *mut ChessBoard) {
+    guard((), || {
+        if !board.is_null() {
+            drop(Box::from_raw(board));
+        }
+    })
+}
+
+/// Replaces the position and forgets the moves made, the board is unchanged on errors
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_load_fen(
+    board: *mut ChessBoard,
+    fen: *const c_char,
+) -> ChessError {
+    guard(ChessError::Internal, || {
+        let board = match board.as_mut() {
+            Some(board) => board,
+            None => return ChessError::NullPointer,
+        };
+        if fen.is_null() {
+            return ChessError::NullPointer;
+        }
+        match read_text(fen).map_or(Err(BoardError::InvalidFen), Board::from_fen) {
+            Ok(position) => {
+                board.game = Game::from_board(position);
+                ChessError::Ok
+            }
+            Err(err) => err.into(),
+        }
+    })
+}
+
+/// Writes the legal moves into moves, at most capacity of them, and returns how
+/// many there are. Call it with a null moves to find out how large it has to be.
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_legal_moves(
+    board: *const ChessBoard,
+    moves: *mut ChessMove,
+    capacity: usize,
+) -> usize {
+    guard(0, || {
+        let board = match board.as_ref() {
+            Some(board) => board,
+            None => return 0,
+        };
+        let legal = board.game.board().generate_legal_moves();
+        if !moves.is_null() {
+            for (i, &mv) in legal.iter().take(capacity).enumerate() {
+                *moves.add(i) = mv.into();
+            }
+        }
+        legal.len()
+    })
+}
+
+/// Parses a legal move in SAN, e.g. "Nf3", or UCI notation, e.g. "g1f3", into
+/// mv. Returns CHESS_ERROR_ILLEGAL_MOVE for a move that can't be made now.
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_parse_move(
+    board: *const ChessBoard,
+    notation: *const c_char,
+    mv: *mut ChessMove,
+) -> ChessError {
+    guard(ChessError::Internal, || {
+        let (board, out) = match (board.as_ref(), mv.as_mut()) {
+            (Some(board), Some(out)) if !notation.is_null() => (board, out),
+            _ => return ChessError::NullPointer,
+        };
+        let parsed = read_text(notation).map_or(Err(BoardError::InvalidMoveNotation), |notation| {
+            board.game.board().parse_move(notation)
+        });
+        match parsed {
+            Ok(parsed) => {
+                *out = parsed.into();
+                ChessError::Ok
+            }
+            Err(err) => err.into(),
+        }
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_make_move(
+    board: *mut ChessBoard,
+    mv: ChessMove,
+) -> ChessError {
+    guard(ChessError::Internal, || {
+        let board = match board.as_mut() {
+            Some(board) => board,
+            None => return ChessError::NullPointer,
+        };
+        match Move::try_from(mv).and_then(|mv| board.game.make_move(&mv)) {
+            Ok(()) => ChessError::Ok,
+            Err(err) => err.into(),
+        }
+    })
+}
+
+/// Takes back the last move into mv, which may be null, and returns false if there was none
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_unmake_move(
+    board: *mut ChessBoard,
+    mv: *mut ChessMove,
+) -> bool {
+    guard(false, || {
+        let undone = match board.as_mut() {
+            Some(board) => board.game.undo(),
+            None => None,
+        };
+        match (undone, mv.as_mut()) {
+            (Some(undone), Some(out)) => *out = undone.into(),
+            (None, _) => return false,
+            _ => {}
+        }
+        true
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_status(board: *const ChessBoard) -> ChessStatus {
+    guard(ChessStatus::Ongoing, || match board.as_ref() {
+        Some(board) => board.game.status().into(),
+        None => ChessStatus::Ongoing,
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_turn(board: *const ChessBoard) -> ChessColor {
+    guard(ChessColor::White, || {
+        match board.as_ref().map(|board| board.game.board().whose_turn()) {
+            Some(Color::Black) => ChessColor::Black,
+            _ => ChessColor::White,
+        }
+    })
+}
+
+/// Writes the FEN of the position into buffer like snprintf(): at most
+/// capacity - 1 characters and a terminating zero. Returns the length of the
+/// whole FEN, so a result >= capacity means it was cut short.
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_fen(
+    board: *const ChessBoard,
+    buffer: *mut c_char,
+    capacity: usize,
+) -> usize {
+    guard(0, || match board.as_ref() {
+        Some(board) => write_text(&board.game.board().to_fen(), buffer, capacity),
+        None => write_text("", buffer, capacity),
+    })
+}
+
+/// The SAN of a legal move, e.g. "exd8=Q+", written like chess_board_fen(). 0 for illegal moves
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_move_san(
+    board: *const ChessBoard,
+    mv: ChessMove,
+    buffer: *mut c_char,
+    capacity: usize,
+) -> usize {
+    guard(0, || {
+        let position = match board.as_ref() {
+            Some(board) => board.game.board(),
+            None => return write_text("", buffer, capacity),
+        };
+        match Move::try_from(mv) {
+            Ok(mv) if position.is_legal(&mv) => {
+                write_text(&position.move_to_san(&mv), buffer, capacity)
+            }
+            _ => write_text("", buffer, capacity),
+        }
+    })
+}
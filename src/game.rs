@@ -0,0 +1,316 @@
+use board::*;
+use piece::Color;
+use rand::Rng;
+
+mod pgn;
+pub use self::pgn::*;
+
+// Why a game ended
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    // A player claimed the draw, see Game::claim_draw()
+    FiftyMoveRule,
+    SeventyFiveMoveRule,
+    InsufficientMaterial,
+    ThreefoldRepetition,
+    Resignation,
+    // The players agreed to a draw
+    Agreement,
+    // A player ran out of time, a draw if the opponent couldn't have checkmated them
+    Timeout,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct GameResult {
+    // None if the game was drawn
+    pub winner: Option<Color>,
+    pub termination: Termination,
+}
+
+impl GameResult {
+    // "1-0", "0-1" or "1/2-1/2"
+    pub fn to_pgn_str(&self) -> &'static str {
+        match self.winner {
+            Some(Color::White) => "1-0",
+            Some(Color::Black) => "0-1",
+            None => "1/2-1/2",
+        }
+    }
+}
+
+/*
+ * A Board together with the moves that led to it. Knows about repeated
+ * positions, can take back moves and ends when the game is over, either
+ * by the rules or because a player resigned or the players agreed to a draw.
+ */
+#[derive(Debug, Clone)]
+pub struct Game {
+    start: Board,
+    board: Board,
+    moves: Vec<Move>,
+    // The position before each of the moves
+    positions: Vec<Board>,
+    result: Option<GameResult>,
+    // PGN tag pairs, e.g. ("White", "Carlsen")
+    tags: Vec<(String, String)>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::from_board(Board::new())
+    }
+}
+
+// The tags every PGN file has, in the order they are written
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+impl Game {
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn from_board(board: Board) -> Self {
+        let mut game = Game {
+            start: board,
+            board,
+            moves: Vec::new(),
+            positions: Vec::new(),
+            result: None,
+            tags: SEVEN_TAG_ROSTER
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        };
+        game.update_result();
+        game
+    }
+
+    #[inline]
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    #[inline]
+    pub fn start_position(&self) -> &Board {
+        &self.start
+    }
+
+    #[inline]
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    #[inline]
+    pub fn last_move(&self) -> Option<Move> {
+        self.moves.last().copied()
+    }
+
+    // Like Board::status(), but also detects threefold repetition
+    pub fn status(&self) -> GameStatus {
+        let status = self.board.status();
+        if !status.is_game_over() && self.repetition_count() >= 3 {
+            return GameStatus::ThreefoldRepetition;
+        }
+        status
+    }
+
+    // How many times the current position has occurred, including now
+    pub fn repetition_count(&self) -> usize {
+        1 + self
+            .positions
+            .iter()
+            .filter(|position| position.is_same_position(&self.board))
+            .count()
+    }
+
+    #[inline]
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    #[inline]
+    pub fn is_over(&self) -> bool {
+        self.result.is_some()
+    }
+
+    // Returns GameOver once the game has ended, otherwise the same as Board::make_move()
+    pub fn make_move(&mut self, mv: &Move) -> Result<(), BoardError> {
+        if self.is_over() {
+            return Err(BoardError::GameOver);
+        }
+        let before = self.board;
+        self.board.make_move(mv)?;
+        self.positions.push(before);
+        self.moves.push(*mv);
+        self.update_result();
+        Ok(())
+    }
+
+    // Takes back the last move, which also takes back resignations and draw agreements
+    pub fn undo(&mut self) -> Option<Move> {
+        let mv = self.moves.pop()?;
+        self.board = self.positions.pop().unwrap();
+        self.result = None;
+        self.update_result();
+        Some(mv)
+    }
+
+    pub fn resign(&mut self, color: Color) {
+        if !self.is_over() {
+            self.result = Some(GameResult {
+                winner: Some(color.flip()),
+                termination: Termination::Resignation,
+            });
+        }
+    }
+
+    pub fn agree_draw(&mut self) {
+        if !self.is_over() {
+            self.result = Some(GameResult {
+                winner: None,
+                termination: Termination::Agreement,
+            });
+        }
+    }
+
+    // Ends the game in a draw under the fifty move rule, false if it can't be claimed
+    pub fn claim_draw(&mut self) -> bool {
+        if self.is_over() || !self.board.can_claim_fifty_move_draw() {
+            return false;
+        }
+        self.result = Some(GameResult {
+            winner: None,
+            termination: Termination::FiftyMoveRule,
+        });
+        true
+    }
+
+    // color ran out of time, see Board::can_checkmate() for when this is a draw
+    pub fn time_forfeit(&mut self, color: Color) {
+        if !self.is_over() {
+            let opponent = color.flip();
+            self.result = Some(GameResult {
+                winner: Some(opponent).filter(|&winner| self.board.can_checkmate(winner)),
+                termination: Termination::Timeout,
+            });
+        }
+    }
+
+    fn update_result(&mut self) {
+        let termination = match self.status() {
+            GameStatus::Ongoing | GameStatus::Check => return,
+            GameStatus::Checkmate => Termination::Checkmate,
+            GameStatus::Stalemate => Termination::Stalemate,
+            GameStatus::SeventyFiveMoveRule => Termination::SeventyFiveMoveRule,
+            GameStatus::InsufficientMaterial => Termination::InsufficientMaterial,
+            GameStatus::ThreefoldRepetition => Termination::ThreefoldRepetition,
+        };
+        self.result = Some(GameResult {
+            winner: match termination {
+                Termination::Checkmate => Some(self.board.whose_turn().flip()),
+                _ => None,
+            },
+            termination,
+        });
+    }
+
+    /*
+     * Sets a PGN tag such as "White" or "Event". The Result tag is only used
+     * while the game itself hasn't ended, e.g. for games read from PGN that were resigned.
+     */
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // The game in Portable Game Notation, with the moves written in SAN
+    pub fn to_pgn(&self) -> String {
+        let result = match self.result {
+            Some(result) => result.to_pgn_str(),
+            None => self
+                .tag("Result")
+                .filter(|tag| ["1-0", "0-1", "1/2-1/2"].contains(tag))
+                .unwrap_or("*"),
+        };
+
+        let mut pgn = String::new();
+        for (name, value) in self.tags.iter() {
+            let value = match name.as_str() {
+                "Result" => result,
+                _ => value,
+            };
+            pgn.push_str(&format!(
+                "[{} \"{}\"]\n",
+                name,
+                value.replace('\\', "\\\\").replace('"', "\\\"")
+            ));
+        }
+        if self.start != Board::new() {
+            pgn.push_str(&format!(
+                "[SetUp \"1\"]\n[FEN \"{}\"]\n",
+                self.start.to_fen()
+            ));
+        }
+        pgn.push('\n');
+
+        let mut tokens = Vec::new();
+        for (i, (mv, position)) in self.moves.iter().zip(self.positions.iter()).enumerate() {
+            let number = position.fullmove_number();
+            match position.whose_turn() {
+                Color::White => tokens.push(format!("{}.", number)),
+                Color::Black if i == 0 => tokens.push(format!("{}...", number)),
+                Color::Black => {}
+            }
+            tokens.push(position.move_to_san(mv));
+        }
+        tokens.push(result.to_string());
+
+        // Lines in PGN files should be at most 80 characters long
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > 80 {
+                pgn.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                pgn.push(' ');
+                line_length += 1;
+            }
+            line_length += token.len();
+            pgn.push_str(&token);
+        }
+        pgn.push('\n');
+        pgn
+    }
+}
+
+// A game from the initial position with random legal moves, until it ends or max_plies moves are made
+pub fn random_game<R: Rng + ?Sized>(rng: &mut R, max_plies: usize) -> Game {
+    let mut game = Game::new();
+    while game.moves().len() < max_plies {
+        match game.board().random_legal_move(rng) {
+            Some(mv) if !game.is_over() => game.make_move(&mv).unwrap(),
+            _ => break,
+        }
+    }
+    game
+}
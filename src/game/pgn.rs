@@ -0,0 +1,255 @@
+use board::*;
+use game::Game;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token<'a> {
+    Tag(&'a str, String),
+    Move(&'a str),
+    // "1-0", "0-1", "1/2-1/2" or "*"
+    Result(&'a str),
+}
+
+// Splits PGN into tags, moves and results, skipping comments, variations and annotations
+#[derive(Debug, Clone)]
+struct Tokenizer<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    // Moves past the next occurrence of end, or to the end of the text
+    fn skip_past(&mut self, end: u8) {
+        match self.text.as_bytes()[self.pos..]
+            .iter()
+            .position(|&c| c == end)
+        {
+            Some(i) => self.pos += i + 1,
+            None => self.pos = self.text.len(),
+        }
+    }
+
+    fn skip_variation(&mut self) {
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                b'(' => depth += 1,
+                b')' if depth == 1 => return,
+                b')' => depth -= 1,
+                b'{' => self.skip_past(b'}'),
+                b';' => self.skip_past(b'\n'),
+                _ => {}
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    // A tag pair like [Event "Casual game"], the backslash escapes '"' and itself
+    fn tag(&mut self) -> Result<Token<'a>, BoardError> {
+        self.pos += 1;
+        self.skip_whitespace();
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_')
+        {
+            self.pos += 1;
+        }
+        let name = &self.text[start..self.pos];
+        self.skip_whitespace();
+        if name.is_empty() || self.peek() != Some(b'"') {
+            self.skip_past(b']');
+            return Err(BoardError::InvalidPgn);
+        }
+        self.pos += 1;
+
+        let mut value = String::new();
+        let mut chars = self.text[self.pos..].char_indices();
+        loop {
+            match chars.next() {
+                Some((_, '\\')) => value.extend(chars.next().map(|(_, c)| c)),
+                Some((i, '"')) => {
+                    self.pos += i + 1;
+                    break;
+                }
+                Some((_, c)) => value.push(c),
+                None => {
+                    self.pos = self.text.len();
+                    return Err(BoardError::InvalidPgn);
+                }
+            }
+        }
+        self.skip_whitespace();
+        if self.peek() != Some(b']') {
+            self.skip_past(b']');
+            return Err(BoardError::InvalidPgn);
+        }
+        self.pos += 1;
+        Ok(Token::Tag(name, value))
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<Token<'a>, BoardError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line_start = self.pos == 0 || self.text.as_bytes()[self.pos - 1] == b'\n';
+            match self.peek()? {
+                c if c.is_ascii_whitespace() => self.pos += 1,
+                b'{' => self.skip_past(b'}'),
+                b';' => self.skip_past(b'\n'),
+                // Escaped lines that other programs use for their own data
+                b'%' if line_start => self.skip_past(b'\n'),
+                b'(' => self.skip_variation(),
+                // Stray closing brackets are ignored like the rest of a broken variation
+                b')' | b'}' | b']' => self.pos += 1,
+                b'[' => return Some(self.tag()),
+                _ => {
+                    let start = self.pos;
+                    while self
+                        .peek()
+                        .is_some_and(|c| !c.is_ascii_whitespace() && !b"{}()[];".contains(&c))
+                    {
+                        self.pos += 1;
+                    }
+                    let word = &self.text[start..self.pos];
+                    if let "1-0" | "0-1" | "1/2-1/2" | "*" = word {
+                        return Some(Ok(Token::Result(word)));
+                    }
+                    // Numeric annotation glyphs like $1
+                    if word.starts_with('$') {
+                        continue;
+                    }
+                    // Move numbers, which can be glued to the move as in "1.e4"
+                    let word = match word.find(|c: char| !c.is_ascii_digit()) {
+                        Some(i) if word[i..].starts_with('.') => word[i..].trim_start_matches('.'),
+                        _ => word,
+                    };
+                    if !word.is_empty() {
+                        return Some(Ok(Token::Move(word)));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/*
+ * Reads the games of a PGN file one after another. A game that can't be read
+ * is returned as an error and reading continues with the next game.
+ */
+#[derive(Debug, Clone)]
+pub struct PgnReader<'a> {
+    tokens: Tokenizer<'a>,
+    // A tag that was read while looking for the end of the previous game
+    pending: Option<Token<'a>>,
+}
+
+impl<'a> PgnReader<'a> {
+    pub fn new(pgn: &'a str) -> Self {
+        PgnReader {
+            tokens: Tokenizer { text: pgn, pos: 0 },
+            pending: None,
+        }
+    }
+}
+
+impl<'a> Iterator for PgnReader<'a> {
+    type Item = Result<Game, BoardError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut tags = Vec::new();
+        let mut game: Option<Result<Game, BoardError>> = None;
+        let mut error = None;
+
+        loop {
+            let token = match self.pending.take() {
+                Some(token) => token,
+                None => match self.tokens.next() {
+                    Some(Ok(token)) => token,
+                    Some(Err(err)) => {
+                        error.get_or_insert(err);
+                        continue;
+                    }
+                    None if tags.is_empty() && game.is_none() && error.is_none() => return None,
+                    None => break,
+                },
+            };
+            match token {
+                // The tags of the next game, this one ended without a result
+                Token::Tag(..) if game.is_some() => {
+                    self.pending = Some(token);
+                    break;
+                }
+                Token::Tag(name, value) => tags.push((name, value)),
+                Token::Move(san) => {
+                    let game = game.get_or_insert_with(|| start(&tags));
+                    if let Ok(ref mut current) = *game {
+                        if let Err(err) = play(current, san) {
+                            *game = Err(err);
+                        }
+                    }
+                }
+                Token::Result(result) => {
+                    game.get_or_insert_with(|| start(&tags));
+                    tags.push(("Result", result.to_string()));
+                    break;
+                }
+            }
+        }
+
+        let mut game = match (error, game) {
+            (Some(err), _) => return Some(Err(err)),
+            (None, Some(game)) => game,
+            (None, None) => start(&tags),
+        };
+        if let Ok(ref mut game) = game {
+            for (name, value) in tags {
+                game.set_tag(name, &value);
+            }
+            game.update_result();
+        }
+        Some(game)
+    }
+}
+
+// The game before the first move, starting from the FEN tag if there is one
+fn start(tags: &[(&str, String)]) -> Result<Game, BoardError> {
+    match tags.iter().find(|(name, _)| *name == "FEN") {
+        Some((_, fen)) => Ok(Game::from_board(Board::from_fen(fen)?)),
+        None => Ok(Game::new()),
+    }
+}
+
+/*
+ * Plays a move without ending the game, since a PGN file may continue
+ * after a repetition or the fifty move rule when no player claimed the draw
+ */
+fn play(game: &mut Game, san: &str) -> Result<(), BoardError> {
+    let mv = game.board.parse_san(san)?;
+    let before = game.board;
+    game.board.make_move(&mv)?;
+    game.positions.push(before);
+    game.moves.push(mv);
+    Ok(())
+}
+
+impl Game {
+    // Reads the first game of the PGN text, use PgnReader for files with more than one
+    pub fn from_pgn(pgn: &str) -> Result<Game, BoardError> {
+        PgnReader::new(pgn)
+            .next()
+            .unwrap_or(Err(BoardError::InvalidPgn))
+    }
+}
@@ -0,0 +1,82 @@
+/*
+ * The rules every position has to keep, shared by the property tests and the
+ * fuzz targets in fuzz/ so that both check the same things. Violations panic
+ * with the FEN of the position.
+ */
+use board::{Board, Move};
+use piece::{Piece, PieceType};
+use square::Square;
+use std::collections::HashSet;
+
+// Every move that is_legal() accepts, found by trying all of them
+pub fn brute_force_moves(board: &Board) -> HashSet<Move> {
+    let promotions = [
+        None,
+        Some(PieceType::Pawn),
+        Some(PieceType::Knight),
+        Some(PieceType::Bishop),
+        Some(PieceType::Rook),
+        Some(PieceType::Queen),
+        Some(PieceType::King),
+    ];
+    let mut moves = HashSet::new();
+    for from in Square::ALL {
+        for to in Square::ALL {
+            for promotion in promotions {
+                let mv = Move::new(from, to, promotion);
+                if board.is_legal(&mv) {
+                    moves.insert(mv);
+                }
+            }
+        }
+    }
+    moves
+}
+
+pub fn check_position(board: &Board) {
+    let fen = board.to_fen();
+    let moves = board.generate_legal_moves();
+    let generated: HashSet<Move> = moves.iter().copied().collect();
+    assert_eq!(generated.len(), moves.len(), "{}", fen);
+    assert_eq!(generated, brute_force_moves(board), "{}", fen);
+
+    // The other ways of listing moves agree
+    let from: HashSet<Move> = Square::ALL
+        .iter()
+        .flat_map(|&square| board.legal_moves_from(square))
+        .collect();
+    assert_eq!(from, generated, "{}", fen);
+    let to: HashSet<Move> = Square::ALL
+        .iter()
+        .flat_map(|&square| board.legal_moves_to(square))
+        .collect();
+    assert_eq!(to, generated, "{}", fen);
+    assert_eq!(board.has_legal_moves(), !moves.is_empty(), "{}", fen);
+
+    assert_eq!(Board::from_fen(&fen).as_ref(), Ok(board));
+
+    let turn = board.whose_turn();
+    let king = Piece {
+        piece_type: PieceType::King,
+        color: turn,
+    };
+    for mv in moves.iter() {
+        let san = board.move_to_san(mv);
+        assert_eq!(board.parse_san(&san), Ok(*mv), "{} {}", fen, san);
+        assert_eq!(Move::from_uci(&mv.to_string()), Ok(*mv), "{} {}", fen, san);
+
+        // A legal move never leaves the own king attacked
+        let mut after = *board;
+        after.make_move(mv).unwrap();
+        let king_square = Square::ALL
+            .iter()
+            .find(|&&square| after.piece_at(square) == Some(king))
+            .unwrap();
+        assert!(
+            after.attackers_of(*king_square, turn.flip()).is_empty(),
+            "{} {}",
+            fen,
+            san
+        );
+    }
+}
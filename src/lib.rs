@@ -1,18 +1,64 @@
+// Only for the crate name, which predates the snake case convention. Every module
+// below turns the lint back on.
+#![allow(non_snake_case)]
+
 #[macro_use]
+#[warn(non_snake_case)]
 pub mod board;
+#[warn(non_snake_case)]
+pub mod book;
+#[warn(non_snake_case)]
+pub mod clock;
+#[warn(non_snake_case)]
+pub mod ffi;
+#[warn(non_snake_case)]
+pub mod game;
+#[cfg(any(test, feature = "fuzzing"))]
+#[warn(non_snake_case)]
+pub mod invariants;
+#[warn(non_snake_case)]
+pub mod openings;
+#[warn(non_snake_case)]
+pub mod perft;
+#[warn(non_snake_case)]
 pub mod piece;
+#[warn(non_snake_case)]
+pub mod puzzle;
+#[warn(non_snake_case)]
+pub mod render;
+#[warn(non_snake_case)]
+pub mod search;
+#[warn(non_snake_case)]
+pub mod solve;
+#[warn(non_snake_case)]
+pub mod square;
+#[cfg(feature = "syzygy")]
+#[warn(non_snake_case)]
+pub mod syzygy;
+#[warn(non_snake_case)]
+pub mod tablebase;
+#[cfg(test)]
+extern crate proptest;
 extern crate rand;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+#[warn(non_snake_case)]
+mod serialize;
 
+#[cfg(test)]
 use board::{Move, Position};
 
+#[cfg(test)]
 #[inline]
 fn to_cordinate(c: char) -> i8 {
-    if c >= 'a' && c <= 'h' {
+    if ('a'..='h').contains(&c) {
         return c as i8 - 'a' as i8;
     }
     7 - (c as i8 - '1' as i8)
 }
 
+#[cfg(test)]
 #[inline]
 fn convert(mv: &str) -> Move {
     let x: Vec<char> = mv.chars().collect();
@@ -30,21 +76,28 @@ fn convert(mv: &str) -> Move {
 }
 
 #[cfg(test)]
+#[warn(non_snake_case)]
 mod tests {
     use board::*;
+    use game::Game;
+    use invariants::check_position;
     use piece::PieceType;
-    use std::fmt::Debug;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use proptest::test_runner::RngSeed;
+    use square::Square;
+
+    use convert;
     use std::mem::swap;
-    use {board, convert};
 
     // Test according to Shannon number
     #[test]
     fn test() {
         let mut prev: Vec<Board> = vec![Board::new()];
         let mut curr: Vec<Board> = vec![];
-        let expected = vec![20, 400, 8902, 197281, 4865609];
-        let ep_expected = vec![0, 0, 0, 0, 258];
-        let cm_expected = vec![0, 0, 0, 8, 347];
+        let expected = [20, 400, 8902, 197281, 4865609];
+        let ep_expected = [0, 0, 0, 0, 258];
+        let cm_expected = [0, 0, 0, 8, 347];
 
         let mut ep;
         let mut cm;
@@ -58,11 +111,12 @@ mod tests {
             for board in &prev {
                 let moves = board.generate_legal_moves();
                 for mv in moves {
-                    let mut x = board.clone();
-                    if at!(board, mv.from).as_ref().unwrap().piece_type == PieceType::Pawn {
-                        if mv.to.col - mv.from.col != 0 && at!(board, mv.to).as_ref().is_none() {
-                            ep += 1;
-                        }
+                    let mut x = *board;
+                    if at!(board, mv.from).as_ref().unwrap().piece_type == PieceType::Pawn
+                        && mv.to.col - mv.from.col != 0
+                        && at!(board, mv.to).as_ref().is_none()
+                    {
+                        ep += 1;
                     }
                     x.make_move(&mv).unwrap();
                     if x.is_checkmate() {
@@ -96,7 +150,7 @@ mod tests {
         for mv in moves {
             board.print_board();
             println!();
-            assert_eq!(board.is_legal(&convert(mv)), true);
+            assert!(board.is_legal(&convert(mv)));
             board.make_move(&convert(mv)).unwrap();
         }
         board.print_board();
@@ -111,31 +165,31 @@ mod tests {
         for mv in moves {
             board.print_board();
             println!();
-            assert_eq!(board.is_legal(&convert(mv)), true);
+            assert!(board.is_legal(&convert(mv)));
             board.make_move(&convert(mv)).unwrap();
         }
         board.print_board();
 
-        assert_eq!(board.is_legal(&convert("e8c8")), false);
+        assert!(!board.is_legal(&convert("e8c8")));
 
         moves = vec!["h8h7", "g2g4", "h4g3", "f2g3"];
 
         for mv in moves {
             board.print_board();
             println!();
-            assert_eq!(board.is_legal(&convert(mv)), true);
+            assert!(board.is_legal(&convert(mv)));
             board.make_move(&convert(mv)).unwrap();
         }
 
-        assert_eq!(board.is_legal(&convert("e8g8")), false);
+        assert!(!board.is_legal(&convert("e8g8")));
 
-        assert_eq!(board.is_legal(&convert("c5e3")), true);
+        assert!(board.is_legal(&convert("c5e3")));
         board.make_move(&convert("c5e3")).unwrap();
         assert_eq!(board.generate_legal_moves().len(), 4);
 
         board.make_move(&convert("c1b1")).unwrap();
 
-        assert_eq!(board.is_legal(&convert("e8f7")), false);
+        assert!(!board.is_legal(&convert("e8f7")));
 
         board.make_move(&convert("h7h5")).unwrap();
         board.make_move(&convert("e2h5")).unwrap();
@@ -144,7 +198,7 @@ mod tests {
         assert_eq!(board.generate_legal_moves().len(), 2);
         board.make_move(&convert("e8f8")).unwrap();
         board.make_move(&convert("h5g6")).unwrap();
-        assert_eq!(board.is_legal(&convert("b7b7")), false);
+        assert!(!board.is_legal(&convert("b7b7")));
         board.make_move(&convert("b7b6")).unwrap();
         board.make_move(&convert("h2h4")).unwrap();
         board.make_move(&convert("b6b5")).unwrap();
@@ -163,7 +217,1492 @@ mod tests {
         board.print_board();
         board.make_move(&convert("h8g8")).unwrap();
         assert_eq!(board.generate_legal_moves().len(), 0);
-        assert_eq!(board.is_checkmate(), true);
-        assert_eq!(board.is_stalemate(), false);
+        assert!(board.is_checkmate());
+        assert!(!board.is_stalemate());
+    }
+
+    #[test]
+    fn square_conversions() {
+        use square::Square;
+        use std::convert::TryFrom;
+
+        assert_eq!(Square::from_algebraic("e4"), Some(Square::E4));
+        assert_eq!(Square::from_algebraic("i1"), None);
+        assert_eq!(Square::E4.to_string(), "e4");
+        assert_eq!((Square::E4.file(), Square::E4.rank()), (4, 3));
+
+        assert_eq!(Position::from(Square::A8), Position { row: 0, col: 0 });
+        assert_eq!(Position::from(Square::H1), Position { row: 7, col: 7 });
+        for square in Square::ALL.iter() {
+            assert_eq!(Square::try_from(square.to_position()), Ok(*square));
+        }
+        assert_eq!(
+            Square::try_from(Position { row: 8, col: 0 }),
+            Err(BoardError::InvalidSquare)
+        );
+
+        assert_eq!(Square::H4.right(), None);
+        assert_eq!(Square::E4.up(), Some(Square::E5));
+        assert_eq!(Square::B1.offset(1, 2), Some(Square::C3));
+
+        let board = Board::new();
+        assert_eq!(
+            board.get_board()[Square::E1],
+            board.piece_at(WHITE_KING_STARTING_POS)
+        );
+        assert!(board.is_legal(&Move::new(Square::E2, Square::E4, None)));
+        // at!() evaluates its position once
+        let mut evaluated = 0;
+        let king = at!(board, {
+            evaluated += 1;
+            Square::E1
+        });
+        assert_eq!((king, evaluated), (board.piece_at(Square::E1), 1));
+    }
+
+    #[test]
+    fn legal_moves_per_square_and_type() {
+        use square::Square;
+
+        let mut board = Board::new();
+        let mut knight_moves = board.legal_moves_from(Square::G1);
+        knight_moves.sort_by_key(|mv| mv.to.col);
+        assert_eq!(
+            knight_moves,
+            vec![
+                Move::new(Square::G1, Square::F3, None),
+                Move::new(Square::G1, Square::H3, None)
+            ]
+        );
+        // Not our turn to move these, and nothing stands on e4
+        assert!(board.legal_moves_from(Square::G8).is_empty());
+        assert!(board.legal_moves_from(Square::E4).is_empty());
+
+        assert_eq!(board.legal_moves_of_type(PieceType::Pawn).len(), 16);
+        assert_eq!(board.legal_moves_of_type(PieceType::Knight).len(), 4);
+        assert!(board.legal_moves_of_type(PieceType::Queen).is_empty());
+
+        board.make_move(&convert("e2e4")).unwrap();
+        board.make_move(&convert("d7d5")).unwrap();
+        assert_eq!(
+            board.legal_moves_to(Square::D5),
+            vec![Move::new(Square::E4, Square::D5, None)]
+        );
+        board.make_move(&convert("g1f3")).unwrap();
+        board.make_move(&convert("a7a6")).unwrap();
+        board.make_move(&convert("b1c3")).unwrap();
+        board.make_move(&convert("a6a5")).unwrap();
+        let from: Vec<Position> = board
+            .legal_moves_to(Square::D5)
+            .iter()
+            .map(|mv| mv.from)
+            .collect();
+        assert_eq!(from.len(), 2);
+        assert!(from.contains(&Square::E4.to_position()));
+        assert!(from.contains(&Square::C3.to_position()));
+    }
+
+    #[test]
+    fn attackers_and_attack_maps() {
+        use piece::Color;
+        use square::Square;
+
+        let mut board = Board::new();
+        let white = board.attack_map(Color::White);
+        // f3 and e4
+        assert_eq!(white[5][5], 3);
+        assert_eq!(white[4][4], 0);
+        // The queen and the bishop stand next to the king
+        assert_eq!(board.defenders_of(Square::E1).len(), 2);
+        assert!(board.is_square_attacked(Square::F6, Color::Black));
+        assert!(!board.is_square_attacked(Square::F6, Color::White));
+
+        for mv in ["e2e4", "d7d5", "d1g4"] {
+            board.make_move(&convert(mv)).unwrap();
+        }
+        assert_eq!(
+            board.attackers_of(Square::D5, Color::White),
+            vec![Square::E4.to_position()]
+        );
+        assert_eq!(
+            board.attackers_of(Square::D7, Color::White),
+            vec![Square::G4.to_position()]
+        );
+        assert_eq!(
+            board.defenders_of(Square::D5),
+            vec![Square::D8.to_position()]
+        );
+    }
+
+    #[test]
+    fn checkers_pins_and_discovered_attacks() {
+        use piece::Color;
+        use square::Square;
+
+        let mut board = Board::new();
+        for mv in ["d2d4", "e7e6", "c2c4", "f8b4"] {
+            board.make_move(&convert(mv)).unwrap();
+        }
+        assert_eq!(board.checkers(), vec![Square::B4.to_position()]);
+
+        board.make_move(&convert("b1c3")).unwrap();
+        assert!(board.checkers().is_empty());
+        assert_eq!(
+            board.pinned_pieces(Color::White),
+            vec![(Square::C3.to_position(), Square::B4.to_position())]
+        );
+        assert!(board.pinned_pieces(Color::Black).is_empty());
+
+        let discovered = board.discovered_attackers(Color::Black);
+        assert_eq!(discovered.len(), 3);
+        assert!(discovered.contains(&DiscoveredAttack {
+            attacker: Square::D8.to_position(),
+            blocker: Square::D7.to_position(),
+            target: Square::D4.to_position(),
+        }));
+    }
+
+    #[test]
+    fn game_status() {
+        use game::Termination;
+
+        let mut board = Board::new();
+        assert_eq!(board.status(), GameStatus::Ongoing);
+
+        for mv in ["e2e4", "f7f5", "d1h5"] {
+            board.make_move(&convert(mv)).unwrap();
+        }
+        assert_eq!(board.status(), GameStatus::Check);
+
+        // Fool's mate
+        board = Board::new();
+        for mv in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+            board.make_move(&convert(mv)).unwrap();
+        }
+        let (status, moves) = board.status_and_moves();
+        assert_eq!(status, GameStatus::Checkmate);
+        assert!(moves.is_empty());
+        assert!(board.is_in_check() && board.is_check() && board.is_checkmate());
+        assert!(status.is_game_over() && !status.is_draw());
+
+        board = Board::new();
+        for _ in 0..25 {
+            for mv in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                assert!(!board.status().is_game_over());
+                board.make_move(&convert(mv)).unwrap();
+            }
+        }
+        // After 50 moves the draw can be claimed, only after 75 the game is over
+        assert_eq!(board.halfmove_clock(), 100);
+        assert_eq!(board.status(), GameStatus::Ongoing);
+        assert!(board.can_claim_fifty_move_draw());
+        let mut later = board;
+        for _ in 0..12 {
+            for mv in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                later.make_move(&convert(mv)).unwrap();
+            }
+        }
+        later.make_move(&convert("g1f3")).unwrap();
+        assert!(!later.status().is_game_over());
+        later.make_move(&convert("g8f6")).unwrap();
+        assert_eq!(later.status(), GameStatus::SeventyFiveMoveRule);
+        assert!(!later.can_claim_fifty_move_draw());
+        board.make_move(&convert("e2e4")).unwrap();
+        assert_eq!(board.halfmove_clock(), 0);
+        assert!(!board.can_claim_fifty_move_draw());
+
+        let board = Board::from_fen("r3k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+        let mut game = Game::from_board(board);
+        game.make_move(&convert("a1a2")).unwrap();
+        assert!(!game.is_over());
+        assert!(game.claim_draw());
+        let result = game.result().unwrap();
+        assert_eq!(result.termination, Termination::FiftyMoveRule);
+        assert_eq!(result.winner, None);
+        assert!(!Game::new().claim_draw());
+    }
+
+    #[test]
+    fn board_builder_validation() {
+        use piece::{Color, Piece};
+        use square::Square;
+
+        let king = |color| Piece {
+            piece_type: PieceType::King,
+            color,
+        };
+        let rook = |color| Piece {
+            piece_type: PieceType::Rook,
+            color,
+        };
+        let kings = Board::builder()
+            .piece(Square::E1, king(Color::White))
+            .piece(Square::E8, king(Color::Black));
+
+        assert_eq!(
+            kings.clone().build().unwrap().status(),
+            GameStatus::InsufficientMaterial
+        );
+        let krk = kings
+            .clone()
+            .piece(Square::A1, rook(Color::White))
+            .build()
+            .unwrap();
+        assert_eq!(krk.status(), GameStatus::Ongoing);
+        assert_eq!(krk.legal_moves_of_type(PieceType::Rook).len(), 10);
+
+        assert_eq!(
+            Board::builder()
+                .piece(Square::E1, king(Color::White))
+                .build(),
+            Err(BoardError::InvalidKingCount)
+        );
+        assert_eq!(
+            kings.clone().piece(Square::A2, king(Color::White)).build(),
+            Err(BoardError::InvalidKingCount)
+        );
+        assert_eq!(
+            kings
+                .clone()
+                .piece(
+                    Square::A8,
+                    Piece {
+                        piece_type: PieceType::Pawn,
+                        color: Color::White,
+                    }
+                )
+                .build(),
+            Err(BoardError::PawnOnBackRank)
+        );
+        assert_eq!(
+            kings.clone().piece(Square::A8, rook(Color::White)).build(),
+            Err(BoardError::OpponentInCheck)
+        );
+        assert_eq!(
+            kings
+                .clone()
+                .piece(Square::H1, rook(Color::White))
+                .castling(Color::White, true, true)
+                .build(),
+            Err(BoardError::InvalidCastlingRights)
+        );
+
+        let mut board = Board::new();
+        board.make_move(&convert("e2e4")).unwrap();
+        assert_eq!(BoardBuilder::from(&board).build(), Ok(board));
+        assert_eq!(
+            BoardBuilder::from(&board)
+                .en_passant(Some(Square::D3))
+                .build(),
+            Err(BoardError::InvalidEnPassant)
+        );
+        assert_eq!(BoardBuilder::from(&Board::new()).build(), Ok(Board::new()));
+    }
+
+    #[test]
+    fn fen_and_uci() {
+        let mut board = Board::new();
+        assert_eq!(board.to_fen(), STARTING_FEN);
+        assert_eq!(Board::from_fen(STARTING_FEN), Ok(Board::new()));
+
+        for mv in ["e2e4", "c7c5", "g1f3"] {
+            board.make_move(&mv.parse().unwrap()).unwrap();
+        }
+        let fen = "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2";
+        assert_eq!(board.to_fen(), fen);
+        assert_eq!(fen.parse::<Board>(), Ok(board));
+
+        let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        assert_eq!(Board::from_fen(kiwipete).unwrap().to_fen(), kiwipete);
+        let ep = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+        let board = Board::from_fen(ep).unwrap();
+        assert_eq!(board.to_fen(), ep);
+        assert!(board.is_legal(&"e5f6".parse().unwrap()));
+
+        for invalid in [
+            "",
+            "8/8/8/8/8/8/8/8 w - -",
+            "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        ] {
+            assert!(Board::from_fen(invalid).is_err());
+        }
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+            Err(BoardError::InvalidFen)
+        );
+
+        let promotion = Move::from_uci("e7e8q").unwrap();
+        assert_eq!(promotion.promotion, Some(PieceType::Queen));
+        assert_eq!(promotion.to_string(), "e7e8q");
+        assert_eq!(Move::from_uci("e1g1").unwrap(), convert("e1g1"));
+        assert_eq!(
+            Move::from_uci("e7e8k"),
+            Err(BoardError::InvalidMoveNotation)
+        );
+        assert_eq!(Move::from_uci("e9e8"), Err(BoardError::InvalidMoveNotation));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        extern crate serde_json;
+        use piece::{Color, Piece};
+        use square::Square;
+
+        let board = Board::new();
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(json, format!("\"{}\"", STARTING_FEN));
+        assert_eq!(serde_json::from_str::<Board>(&json).unwrap(), board);
+        assert!(serde_json::from_str::<Board>("\"not a fen\"").is_err());
+
+        let mv = Move::new(Square::E7, Square::E8, Some(PieceType::Queen));
+        assert_eq!(serde_json::to_string(&mv).unwrap(), "\"e7e8q\"");
+        assert_eq!(serde_json::from_str::<Move>("\"e7e8q\"").unwrap(), mv);
+
+        let piece = Piece {
+            piece_type: PieceType::Knight,
+            color: Color::Black,
+        };
+        let json = serde_json::to_string(&piece).unwrap();
+        assert_eq!(serde_json::from_str::<Piece>(&json).unwrap(), piece);
+        let pos = Square::C3.to_position();
+        let json = serde_json::to_string(&pos).unwrap();
+        assert_eq!(serde_json::from_str::<Position>(&json).unwrap(), pos);
+        assert_eq!(serde_json::to_string(&Square::C3).unwrap(), "\"c3\"");
+    }
+
+    #[test]
+    fn text_rendering() {
+        use piece::Color;
+        use render::{text, Glyphs, TextOptions};
+
+        let mut board = Board::new();
+        assert_eq!(
+            board.to_string(),
+            "8  r  n  b  q  k  b  n  r \n\
+             7  p  p  p  p  p  p  p  p \n\
+             6  .  .  .  .  .  .  .  . \n\
+             5  .  .  .  .  .  .  .  . \n\
+             4  .  .  .  .  .  .  .  . \n\
+             3  .  .  .  .  .  .  .  . \n\
+             2  P  P  P  P  P  P  P  P \n\
+             1  R  N  B  Q  K  B  N  R \n   \
+             a  b  c  d  e  f  g  h \n"
+        );
+
+        for mv in ["e2e4", "f7f5", "d1h5"] {
+            board.make_move(&convert(mv)).unwrap();
+        }
+        let options = TextOptions {
+            glyphs: Glyphs::Unicode,
+            coordinates: false,
+            orientation: Color::Black,
+            last_move: Some(convert("d1h5")),
+            ..Default::default()
+        };
+        let rendered = text(&board, &options);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 8);
+        // Black's side is at the bottom, so h1 is the top left square
+        assert_eq!(lines[0], " ♖  ♘  ♗  ♔ [·] ♗  ♘  ♖ ");
+        assert_eq!(lines[4], "[♕] ·  ♟  ·  ·  ·  ·  · ");
+        assert_eq!(lines[7], " ♜  ♞  ♝ (♚) ♛  ♝  ♞  ♜ ");
+
+        let colored = text(
+            &board,
+            &TextOptions {
+                ansi_colors: true,
+                ..Default::default()
+            },
+        );
+        assert!(colored.contains("\x1b[0m"));
+        // Marked by the background instead of brackets
+        assert!(!colored.contains("(k)"));
+    }
+
+    #[test]
+    fn svg_rendering() {
+        use piece::Color;
+        use render::{svg, Arrow, Highlight, SvgOptions};
+        use square::Square;
+
+        let mut board = Board::new();
+        for mv in ["e2e4", "f7f5", "d1h5"] {
+            board.make_move(&convert(mv)).unwrap();
+        }
+        let options = SvgOptions {
+            orientation: Color::Black,
+            last_move: Some(convert("d1h5")),
+            highlights: vec![Highlight::new(Square::E8)],
+            arrows: vec![Arrow::new(Square::G7, Square::G6)],
+            ..Default::default()
+        };
+        let image = svg(&board, &options);
+        assert!(image.starts_with("<svg") && image.ends_with("</svg>"));
+        assert_eq!(image.matches("<use ").count(), 32);
+        assert!(image.contains("url(#check)"));
+        assert_eq!(image.matches("<polygon").count(), 1);
+
+        let plain = svg(
+            &Board::new(),
+            &SvgOptions {
+                coordinates: false,
+                ..Default::default()
+            },
+        );
+        assert!(!plain.contains("<text") && !plain.contains("url(#check)"));
+    }
+
+    #[test]
+    fn san_notation() {
+        use board::{BoardError, IllegalMoveReason};
+
+        let mut board = Board::new();
+        assert_eq!(board.move_to_san(&convert("g1f3")), "Nf3");
+        assert_eq!(board.parse_san("Nf3").unwrap(), convert("g1f3"));
+        assert_eq!(board.parse_move("e2e4").unwrap(), convert("e2e4"));
+        assert_eq!(board.parse_move("e2e5"), Err(BoardError::IllegalMove));
+        assert_eq!(board.parse_san("Ke2"), Err(BoardError::IllegalMove));
+        assert_eq!(board.parse_san("Zz9"), Err(BoardError::InvalidMoveNotation));
+
+        for mv in [
+            "e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "b1c3", "f8c5",
+        ] {
+            board.make_move(&convert(mv)).unwrap();
+        }
+        assert_eq!(board.move_to_san(&convert("e1g1")), "O-O");
+        assert_eq!(board.parse_san("O-O").unwrap(), convert("e1g1"));
+        assert_eq!(board.move_to_san(&convert("c4f7")), "Bxf7+");
+        // Both knights can reach e2
+        let board = Board::from_fen("4k3/8/8/8/8/2N3N1/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.move_to_san(&convert("c3e2")), "Nce2");
+        assert_eq!(board.parse_san("Ne2"), Err(BoardError::AmbiguousMove));
+        assert_eq!(board.parse_san("Nge2").unwrap(), convert("g3e2"));
+
+        let board = Board::from_fen("6k1/4Pppp/8/8/8/8/5PPP/3R2K1 w - - 0 1").unwrap();
+        assert_eq!(board.move_to_san(&convert("d1d8")), "Rd8#");
+        assert_eq!(board.parse_san("e8=Q+").unwrap(), {
+            let mut mv = convert("e7e8");
+            mv.promotion = Some(PieceType::Queen);
+            mv
+        });
+        assert_eq!(
+            board.illegal_move_reason(&convert("e7e8")),
+            Some(IllegalMoveReason::MissingPromotion)
+        );
+        assert_eq!(
+            board.illegal_move_reason(&convert("a8a7")),
+            Some(IllegalMoveReason::NoPieceOnSquare)
+        );
+        assert_eq!(
+            board.illegal_move_reason(&convert("g1g2")),
+            Some(IllegalMoveReason::CaptureOwnPiece)
+        );
+        assert_eq!(board.illegal_move_reason(&convert("d1d8")), None);
+    }
+
+    #[test]
+    fn game_record() {
+        use board::BoardError;
+        use game::{Game, Termination};
+        use piece::Color;
+
+        let mut game = Game::new();
+        // The knights go out and back twice, repeating the start position
+        for _ in 0..2 {
+            for mv in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                game.make_move(&convert(mv)).unwrap();
+            }
+        }
+        assert_eq!(game.repetition_count(), 3);
+        let result = game.result().unwrap();
+        assert_eq!(result.termination, Termination::ThreefoldRepetition);
+        assert_eq!(game.make_move(&convert("e2e4")), Err(BoardError::GameOver));
+
+        assert_eq!(game.undo(), Some(convert("f6g8")));
+        assert!(!game.is_over());
+        game.resign(Color::Black);
+        assert_eq!(game.result().unwrap().winner, Some(Color::White));
+        let pgn = game.to_pgn();
+        assert!(pgn.contains("[Result \"1-0\"]"));
+        assert!(pgn.contains("1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3 Nf6 4. Ng1 1-0"));
+    }
+
+    #[test]
+    fn search_finds_tactics() {
+        use search::{search, SearchOptions};
+
+        let options = SearchOptions {
+            depth: 3,
+            ..Default::default()
+        };
+        // Back rank mate
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1").unwrap();
+        let result = search(&board, &options);
+        assert_eq!(result.best_move, Some(convert("d1d8")));
+        assert_eq!(result.mate_in(), Some(1));
+
+        // Free queen
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1").unwrap();
+        assert_eq!(search(&board, &options).best_move, Some(convert("d1d5")));
+    }
+
+    #[test]
+    fn clock_time_controls() {
+        use clock::{Clock, Increment, ManualTime, Period, TimeControl, TimeSource};
+        use piece::Color;
+        use std::cell::Cell;
+        use std::time::Duration;
+
+        let secs = Duration::from_secs;
+        let time = ManualTime::new();
+
+        let mut clock = Clock::with_time_source(TimeControl::fischer(secs(60), secs(2)), &time);
+        clock.start(Color::White);
+        time.advance(secs(10));
+        assert_eq!(clock.remaining(Color::White), secs(50));
+        assert!(clock.press());
+        assert_eq!(clock.remaining(Color::White), secs(52));
+        assert_eq!(clock.whose_turn(), Color::Black);
+        time.advance(secs(60));
+        assert_eq!(clock.flagged(), Some(Color::Black));
+        assert!(!clock.press());
+        assert_eq!(clock.remaining(Color::Black), Duration::ZERO);
+
+        // Bronstein gives back at most the delay, simple delay doesn't count it at all
+        let mut clock = Clock::with_time_source(TimeControl::bronstein(secs(60), secs(5)), &time);
+        clock.start(Color::White);
+        time.advance(secs(3));
+        clock.press();
+        assert_eq!(clock.remaining(Color::White), secs(60));
+        time.advance(secs(8));
+        clock.press();
+        assert_eq!(clock.remaining(Color::Black), secs(57));
+
+        let mut clock =
+            Clock::with_time_source(TimeControl::simple_delay(secs(60), secs(5)), &time);
+        clock.start(Color::White);
+        time.advance(secs(4));
+        assert_eq!(clock.remaining(Color::White), secs(60));
+        time.advance(secs(4));
+        clock.press();
+        assert_eq!(clock.remaining(Color::White), secs(57));
+
+        // 40 moves in 90 minutes, then 30 minutes, with 30 seconds per move from the start
+        let increment = Increment::Fischer(secs(30));
+        let control = TimeControl::new(vec![
+            Period::new(secs(90 * 60)).moves(40).increment(increment),
+            Period::new(secs(30 * 60)).increment(increment),
+        ]);
+        let mut clock = Clock::with_time_source(control, &time);
+        clock.start(Color::White);
+        for _ in 0..39 {
+            time.advance(secs(60));
+            clock.press();
+            clock.press();
+        }
+        assert_eq!(clock.remaining(Color::White), secs(90 * 60 - 39 * 30));
+        time.advance(secs(60));
+        clock.press();
+        assert_eq!(
+            clock.remaining(Color::White),
+            secs(90 * 60 - 40 * 30 + 30 * 60)
+        );
+
+        // A stopped clock doesn't run
+        clock.stop();
+        time.advance(secs(600));
+        assert_eq!(clock.remaining(Color::Black), secs(90 * 60 + 39 * 30));
+        clock.start(Color::Black);
+        time.advance(secs(1));
+        assert_eq!(clock.remaining(Color::Black), secs(90 * 60 + 39 * 30 - 1));
+
+        // Like the real time, every reading is later than the one before
+        struct Ticking(Cell<Duration>);
+        impl TimeSource for Ticking {
+            fn now(&self) -> Duration {
+                self.0.set(self.0.get() + Duration::from_secs(40));
+                self.0.get()
+            }
+        }
+        let time = Ticking(Cell::new(Duration::ZERO));
+        let mut clock = Clock::with_time_source(TimeControl::sudden_death(secs(60)), &time);
+        clock.start(Color::White);
+        assert!(clock.press());
+        assert_eq!(clock.remaining(Color::White), secs(20));
+        let time = Ticking(Cell::new(Duration::ZERO));
+        let mut clock = Clock::with_time_source(TimeControl::sudden_death(secs(30)), &time);
+        clock.start(Color::White);
+        assert!(!clock.press());
+        assert_eq!(clock.flagged(), Some(Color::White));
+        assert_eq!(clock.remaining(Color::White), Duration::ZERO);
+
+        // Switching to the other player charges the time of the interrupted turn
+        let time = ManualTime::new();
+        let mut clock = Clock::with_time_source(TimeControl::fischer(secs(60), secs(2)), &time);
+        clock.start(Color::White);
+        time.advance(secs(10));
+        clock.start(Color::Black);
+        assert_eq!(clock.remaining(Color::White), secs(50));
+        time.advance(secs(5));
+        assert_eq!(clock.remaining(Color::Black), secs(55));
+        time.advance(secs(60));
+        clock.start(Color::White);
+        assert_eq!(clock.flagged(), Some(Color::Black));
+        assert!(!clock.is_running());
+    }
+
+    #[test]
+    fn timed_games() {
+        use board::BoardError;
+        use clock::{Clock, ManualTime, TimeControl, TimeSource, TimedGame};
+        use game::{Game, Termination};
+        use piece::Color;
+        use std::time::Duration;
+
+        let time = ManualTime::new();
+        let control = TimeControl::sudden_death(Duration::from_secs(60));
+
+        let mut game = TimedGame::new(Game::new(), Clock::with_time_source(control.clone(), &time));
+        game.make_move(&convert("e2e4")).unwrap();
+        time.advance(Duration::from_secs(61));
+        assert_eq!(game.make_move(&convert("e7e5")), Err(BoardError::GameOver));
+        let result = game.game().result().unwrap();
+        assert_eq!(result.termination, Termination::Timeout);
+        assert_eq!(result.winner, Some(Color::White));
+        assert!(!game.clock().is_running());
+
+        // The flag falls after the time was checked but before the clock is pressed
+        struct Slow<'a>(&'a ManualTime);
+        impl TimeSource for Slow<'_> {
+            fn now(&self) -> Duration {
+                self.0.advance(Duration::from_secs(30));
+                self.0.now()
+            }
+        }
+        let slow = Slow(&time);
+        let mut game = TimedGame::new(Game::new(), Clock::with_time_source(control.clone(), slow));
+        assert_eq!(game.make_move(&convert("e2e4")), Err(BoardError::GameOver));
+        assert!(game.game().moves().is_empty());
+        let result = game.game().result().unwrap();
+        assert_eq!(result.termination, Termination::Timeout);
+        assert_eq!(result.winner, Some(Color::Black));
+
+        // White can't checkmate with a lone king, so Black running out of time is a draw
+        let board = Board::from_fen("4k3/8/8/8/8/8/7p/4K3 b - - 0 1").unwrap();
+        let mut game = TimedGame::new(
+            Game::from_board(board),
+            Clock::with_time_source(control.clone(), &time),
+        );
+        time.advance(Duration::from_secs(60));
+        let result = game.check_time().unwrap();
+        assert_eq!(result.winner, None);
+
+        // A knight can only mate with the help of an opponent piece
+        let board = Board::from_fen("4k3/8/8/8/8/8/7p/4KN2 w - - 0 1").unwrap();
+        assert!(board.can_checkmate(Color::White));
+        assert!(board.can_checkmate(Color::Black));
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4KN2 w - - 0 1").unwrap();
+        assert!(!board.can_checkmate(Color::White));
+        // Bishops need an opponent piece on the other square color
+        let board = Board::from_fen("4k3/8/8/8/8/8/1b6/4KB2 w - - 0 1").unwrap();
+        assert!(board.can_checkmate(Color::White));
+        let board = Board::from_fen("4k3/8/8/8/8/8/b7/4KB2 w - - 0 1").unwrap();
+        assert!(!board.can_checkmate(Color::White));
+        // A rook or queen can block on either color, so Black losing on time loses
+        let board = Board::from_fen("4k3/8/8/8/8/8/r7/4KB2 w - - 0 1").unwrap();
+        assert!(board.can_checkmate(Color::White));
+        let mut game = Game::from_board(board);
+        game.time_forfeit(Color::Black);
+        assert_eq!(game.result().unwrap().winner, Some(Color::White));
+    }
+
+    #[test]
+    fn polyglot_books() {
+        use book::Book;
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        // The test positions of the Polyglot format description
+        let positions = [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                0x463b96181691fc9c,
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                0x823c9b50fd114196,
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+                0x0756b94461c50fb0,
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+                0x662fafb965db29d4,
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                0x22a48b5a8e47ff78,
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3",
+                0x652a607ca3f242c1,
+            ),
+            (
+                "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4",
+                0x00fdd303c946bdd9,
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
+                0x3c8123ea7b067637,
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4",
+                0x5c3f9b829b279560,
+            ),
+        ];
+        for (fen, key) in positions {
+            assert_eq!(Board::from_fen(fen).unwrap().polyglot_key(), key, "{}", fen);
+        }
+
+        let entry = |key: u64, mv: u16, weight: u16| {
+            let mut bytes = key.to_be_bytes().to_vec();
+            bytes.extend_from_slice(&mv.to_be_bytes());
+            bytes.extend_from_slice(&weight.to_be_bytes());
+            bytes.extend_from_slice(&[0; 4]);
+            bytes
+        };
+        let castling = Board::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        let mut bytes = Vec::new();
+        // e1h1 and e1a1, the castling moves
+        bytes.extend(entry(castling.polyglot_key(), 0x0107, 1));
+        bytes.extend(entry(castling.polyglot_key(), 0x0100, 1));
+        // e2e4, d2d4 and the illegal e2e5
+        bytes.extend(entry(0x463b96181691fc9c, 0x031c, 3));
+        bytes.extend(entry(0x463b96181691fc9c, 0x02db, 1));
+        bytes.extend(entry(0x463b96181691fc9c, 0x0324, 100));
+        let book = Book::from_bytes(&bytes).unwrap();
+        assert_eq!(book.len(), 5);
+        assert!(Book::from_bytes(&bytes[1..]).is_err());
+
+        let moves: Vec<Move> = book
+            .entries(&castling)
+            .iter()
+            .map(|entry| entry.mv)
+            .collect();
+        assert_eq!(moves, vec![convert("e1g1"), convert("e1c1")]);
+
+        let board = Board::new();
+        assert_eq!(book.entries(&board).len(), 2);
+        assert_eq!(book.best_move(&board), Some(convert("e2e4")));
+        let mut rng = StdRng::seed_from_u64(7);
+        let e4 = (0..1000)
+            .filter(|_| book.choose_move(&board, &mut rng) == Some(convert("e2e4")))
+            .count();
+        assert!((650..850).contains(&e4));
+        assert_eq!(
+            book.choose_move(
+                &Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap(),
+                &mut rng
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn pgn_reading() {
+        use game::{Game, PgnReader, Termination};
+        use piece::Color;
+
+        let pgn = r#"[Event "Casual \"blitz\""]
+[White "A"]
+[Black "B"]
+[Result "0-1"]
+
+1. f3 {weak} e5 2.g4?? (2. Kf2 $2 Qh4+ 3. g3) Qh4# 0-1
+
+[Event "Broken"]
+1. e4 e5 2. Ke3 *
+
+[Event "Resigned"]
+[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"]
+
+1. e4 Kd7 ; a comment
+2. Kd2 1-0
+"#;
+        let games: Vec<_> = PgnReader::new(pgn).collect();
+        assert_eq!(games.len(), 3);
+
+        let mate = games[0].as_ref().unwrap();
+        assert_eq!(mate.tag("Event"), Some("Casual \"blitz\""));
+        assert_eq!(mate.moves().len(), 4);
+        let result = mate.result().unwrap();
+        assert_eq!(result.termination, Termination::Checkmate);
+        assert_eq!(result.winner, Some(Color::Black));
+
+        assert!(games[1].is_err());
+
+        let resigned = games[2].as_ref().unwrap();
+        assert_eq!(resigned.result(), None);
+        assert_eq!(resigned.moves().len(), 3);
+        let written = resigned.to_pgn();
+        assert!(
+            written.contains("[Result \"1-0\"]") && written.ends_with("1. e4 Kd7 2. Kd2 1-0\n")
+        );
+        let reread = Game::from_pgn(&written).unwrap();
+        assert_eq!(reread.board(), resigned.board());
+        assert_eq!(reread.to_pgn(), written);
+
+        // Castling written with zeros and games that continue after a repetition nobody claimed
+        let game = Game::from_pgn(
+            "1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3 Nf6 4. Ng1 Ng8 5. e4 e5 6. Nf3 Nc6 7. Bc4 Bc5 8. 0-0 *",
+        )
+        .unwrap();
+        assert_eq!(game.last_move(), Some(convert("e1g1")));
+        assert!(!game.is_over());
+        assert!(Game::from_pgn("").is_err());
+    }
+
+    #[test]
+    fn book_building() {
+        use book::{Book, BookBuilder};
+        use game::Game;
+
+        let pgn =
+            "1. e4 e5 2. Nf3 1-0\n\n1. e4 c5 0-1\n\n1. d4 d5 1/2-1/2\n\n1. e4 e5 2. Nf3 Nc6 *\n";
+        let mut builder = BookBuilder::new().max_ply(3);
+        assert_eq!(builder.add_pgn(pgn), 4);
+
+        let start = Board::new();
+        let stats = builder.stats(&start);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].mv, convert("e2e4"));
+        assert_eq!(
+            (
+                stats[0].count,
+                stats[0].wins,
+                stats[0].draws,
+                stats[0].losses
+            ),
+            (3, 1, 0, 1)
+        );
+        assert_eq!((stats[1].count, stats[1].draws), (1, 1));
+        // Nc6 is the fourth ply
+        let mut board = start;
+        for mv in ["e2e4", "e7e5", "g1f3"] {
+            board.make_move(&convert(mv)).unwrap();
+        }
+        assert!(builder.stats(&board).is_empty());
+
+        // e4 scores 2 for the win, d4 1 for the draw
+        let mut bytes = Vec::new();
+        builder.write_polyglot(&mut bytes).unwrap();
+        let book = Book::from_bytes(&bytes).unwrap();
+        let weights: Vec<_> = book
+            .entries(&start)
+            .iter()
+            .map(|entry| (entry.mv, entry.weight))
+            .collect();
+        assert_eq!(weights, vec![(convert("e2e4"), 2), (convert("d2d4"), 1)]);
+
+        let mut native = Vec::new();
+        builder.write_native(&mut native).unwrap();
+        let mut reread = BookBuilder::read_native(&native[..]).unwrap();
+        assert_eq!(reread.stats(&start), stats);
+        assert!(BookBuilder::read_native(&bytes[..]).is_err());
+
+        // Castling is stored the Polyglot way and read back as the king's move
+        let castling = Game::from_pgn("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. O-O 1-0").unwrap();
+        reread = reread.max_ply(10);
+        reread.add_game(&castling);
+        let book = reread.build();
+        let mut board = start;
+        for mv in ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5"] {
+            board.make_move(&convert(mv)).unwrap();
+        }
+        assert_eq!(book.best_move(&board), Some(convert("e1g1")));
+    }
+
+    #[test]
+    fn opening_classification() {
+        use game::Game;
+        use openings::{all, classify, lookup};
+
+        // Every line of the table is legal and reaches a position of its own
+        let mut named = 0;
+        for opening in all() {
+            let game = Game::from_pgn(opening.moves).unwrap();
+            if lookup(game.board()) == Some(opening) {
+                named += 1;
+            }
+        }
+        assert_eq!(named, all().count());
+
+        let moves = |uci: &str| -> Vec<Move> { uci.split(' ').map(convert).collect() };
+        let ruy = classify(&moves("e2e4 e7e5 g1f3 b8c6 f1b5 g8f6 e1g1")).unwrap();
+        assert_eq!((ruy.eco, ruy.name), ("C65", "Ruy Lopez: Berlin Defense"));
+
+        // 1. Nf3 d5 2. d4 transposes to 1. d4 d5 2. Nf3
+        assert_eq!(classify(&moves("g1f3 d7d5 d2d4")).unwrap().eco, "D02");
+        assert_eq!(
+            classify(&moves("c2c4 e7e6 d2d4 d7d5 b1c3")).unwrap().name,
+            "Queen's Gambit Declined"
+        );
+        assert_eq!(classify(&moves("h2h4 h7h5")), None);
+        assert_eq!(classify(&[]), None);
+        let game = Game::from_pgn("1. d4 Nf6 2. c4 e6 3. Nc3 Bb4 4. a3 Bxc3+ *").unwrap();
+        assert_eq!(game.opening().unwrap().eco, "E20");
+    }
+
+    #[test]
+    fn endgame_tablebases() {
+        use tablebase::*;
+
+        let mut tablebase = Tablebase::new();
+        assert_eq!(tablebase.generate("KQ"), Err(BoardError::InvalidMaterial));
+        // Needs the tables for promoting to a queen or a rook as well
+        tablebase.generate("KPvK").unwrap();
+        let krk = tablebase.table(&"KRK".parse().unwrap()).unwrap();
+        assert_eq!(
+            tablebase
+                .table(&"KQK".parse().unwrap())
+                .unwrap()
+                .longest_mate(),
+            19
+        );
+        assert_eq!(krk.longest_mate(), 31);
+
+        let probe = |fen: &str| tablebase.probe(&Board::from_fen(fen).unwrap()).unwrap();
+        let win = |dtm| Probe { wdl: Wdl::Win, dtm };
+        assert_eq!(probe("6k1/8/6K1/8/8/8/8/R7 w - - 0 1"), win(1));
+        assert_eq!(
+            probe("R5k1/8/6K1/8/8/8/8/8 b - - 0 1"),
+            Probe {
+                wdl: Wdl::Loss,
+                dtm: 0
+            }
+        );
+        // The colors may be the other way around
+        assert_eq!(probe("r7/8/8/8/8/6k1/8/6K1 b - - 0 1"), win(1));
+        assert_eq!(probe("8/8/8/8/8/4k3/4P3/4K3 w - - 0 1").wdl, Wdl::Draw);
+        assert_eq!(probe("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1").wdl, Wdl::Win);
+        assert_eq!(probe("8/8/8/8/4p3/4k3/8/4K3 b - - 0 1").wdl, Wdl::Win);
+        assert_eq!(tablebase.probe(&Board::new()), None);
+
+        // Perfect play shortens the mate by a move with each move
+        let mut board = Board::from_fen("8/8/8/3k4/8/8/8/R3K3 w - - 0 1").unwrap();
+        let before = tablebase.probe(&board).unwrap();
+        assert_eq!(before.wdl, Wdl::Win);
+        board
+            .make_move(&tablebase.best_move(&board).unwrap())
+            .unwrap();
+        let after = tablebase.probe(&board).unwrap();
+        assert_eq!(
+            after,
+            Probe {
+                wdl: Wdl::Loss,
+                dtm: before.dtm - 1
+            }
+        );
+
+        let mut bytes = Vec::new();
+        krk.write(&mut bytes).unwrap();
+        assert_eq!(&Table::read(&bytes[..]).unwrap(), krk);
+        assert!(Table::read(&bytes[..100]).is_err());
+    }
+
+    // Builds five tables of four pieces, over half an hour on one core: cargo test -- --ignored
+    #[test]
+    #[ignore]
+    fn tablebase_captures_and_promotions() {
+        use tablebase::*;
+
+        // Every move of Black is a promotion on f1, and each one allows mate in one
+        let mut tablebase = Tablebase::new();
+        tablebase.generate("KQvKP").unwrap();
+        let board = Board::from_fen("8/8/8/8/8/8/k1K2p2/2Q5 b - - 0 1").unwrap();
+        assert_eq!(
+            tablebase.probe(&board),
+            Some(Probe {
+                wdl: Wdl::Loss,
+                dtm: 2
+            })
+        );
+    }
+
+    #[cfg(feature = "syzygy")]
+    #[test]
+    fn syzygy_tablebases() {
+        use syzygy::*;
+
+        // A KQvK table that says White wins and Black loses wherever it is to move
+        let mut data = vec![0x71, 0xe8, 0x23, 0x5d, 0x01, 0x00, 0x66, 0x55, 0xee, 0x00];
+        data.extend_from_slice(&[0x80, 4, 0x80, 0]);
+        let dir = std::env::temp_dir().join(format!("chess-syzygy-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("KQvK.rtbw"), &data).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a table").unwrap();
+        let syzygy = Syzygy::open(&dir).unwrap();
+        assert_eq!(syzygy.max_pieces(), 3);
+
+        let probe = |fen: &str| syzygy.probe_wdl(&Board::from_fen(fen).unwrap());
+        assert_eq!(probe("8/8/8/8/8/2k5/Q7/4K3 w - - 0 1"), Some(Wdl::Win));
+        assert_eq!(probe("k7/8/1K6/8/8/8/7Q/8 b - - 0 1"), Some(Wdl::Loss));
+        // The colors may be the other way around
+        assert_eq!(probe("8/8/8/8/8/2K5/q7/4k3 b - - 0 1"), Some(Wdl::Win));
+        // Capturing the queen draws, whatever the table says
+        assert_eq!(probe("8/8/8/8/8/8/1Qk5/4K3 b - - 0 1"), Some(Wdl::Draw));
+        assert_eq!(probe("8/8/8/8/8/2k5/8/R3K3 w - - 0 1"), None);
+        assert_eq!(syzygy.probe_wdl(&Board::new()), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+        // Without a DTZ table there is no best move
+        assert_eq!(
+            syzygy.best_move(&Board::from_fen("8/8/8/8/8/2k5/Q7/4K3 w - - 0 1").unwrap()),
+            None
+        );
+    }
+
+    /*
+     * The only test against the official tables, which aren't committed, so
+     * it is ignored. Copy the ones listed in tests/syzygy/README.md there or
+     * point SYZYGY_PATH at them: cargo test --features syzygy -- --ignored
+     */
+    #[cfg(feature = "syzygy")]
+    #[test]
+    #[ignore]
+    fn syzygy_real_tablebases() {
+        use syzygy::*;
+
+        let dir = std::env::var("SYZYGY_PATH")
+            .unwrap_or_else(|_| concat!(env!("CARGO_MANIFEST_DIR"), "/tests/syzygy").to_string());
+        let syzygy = Syzygy::open(dir).unwrap();
+        assert_eq!(syzygy.max_pieces(), 3);
+        let board = |fen: &str| Board::from_fen(fen).unwrap();
+        assert_eq!(
+            syzygy.probe_wdl(&board("8/8/8/8/8/2k5/Q7/4K3 w - - 0 1")),
+            Some(Wdl::Win)
+        );
+        assert_eq!(
+            syzygy.probe_wdl(&board("8/8/8/8/8/4k3/4P3/4K3 w - - 0 1")),
+            Some(Wdl::Draw)
+        );
+        assert_eq!(
+            syzygy.probe_wdl(&board("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1")),
+            Some(Wdl::Win)
+        );
+        assert_eq!(
+            syzygy.probe_dtz(&board("6k1/8/6K1/8/8/8/8/R7 w - - 0 1")),
+            Some(1)
+        );
+        assert_eq!(
+            syzygy.probe_dtz(&board("8/8/8/8/8/4k3/4P3/4K3 w - - 0 1")),
+            Some(0)
+        );
+
+        // Following the best moves from a won position wins
+        let mut board = board("8/8/8/3k4/8/8/8/R3K3 w - - 0 1");
+        for _ in 0..60 {
+            if board.is_checkmate() {
+                break;
+            }
+            board.make_move(&syzygy.best_move(&board).unwrap()).unwrap();
+        }
+        assert!(board.is_checkmate());
+    }
+
+    #[test]
+    fn mate_solver() {
+        use solve::*;
+
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        assert_eq!(mate_in(&board, 1), Some(vec![convert("a1a8")]));
+        assert_eq!(mate_in(&Board::new(), 2), None);
+
+        // Morphy's mate in two: 1. Ra6 bxa6 2. b7#
+        let board = Board::from_fen("kbK5/pp6/1P6/8/8/8/8/R7 w - - 0 1").unwrap();
+        assert_eq!(mate_in(&board, 1), None);
+        let line = mate_in(&board, 2).unwrap();
+        assert_eq!(line.len(), 3);
+        assert_eq!(line[0], convert("a1a6"));
+        let mut after = board;
+        for mv in line.iter() {
+            after.make_move(mv).unwrap();
+        }
+        assert!(after.is_checkmate());
+        for method in [Method::Exhaustive, Method::ProofNumber] {
+            let solution = solve(&board, 2, method).unwrap();
+            assert_eq!(solution.line, line);
+            assert_eq!(solution.keys, vec![convert("a1a6")]);
+            assert!(solution.is_unique());
+        }
+
+        // Cooked: the rook mates on the back rank from either side
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R3R1K1 w - - 0 1").unwrap();
+        for method in [Method::Exhaustive, Method::ProofNumber] {
+            let solution = solve(&board, 1, method).unwrap();
+            assert!(!solution.is_unique());
+            assert_eq!(solution.keys.len(), 2);
+        }
+        assert_eq!(solve(&board, 0, Method::Exhaustive), None);
+    }
+
+    #[test]
+    fn puzzles() {
+        use puzzle::*;
+
+        let fork = Puzzle::new("r3k3/8/8/3N4/8/8/P7/4K3 w - - 0 1", "d5c7 e8d7 c7a8").unwrap();
+        assert_eq!(fork.validate(&PuzzleOptions::default()), Ok(()));
+        assert_eq!(fork.motifs(), vec![Motif::Fork]);
+        let broken = Puzzle::new("r3k3/8/8/3N4/8/8/P7/4K3 w - - 0 1", "Nc7+ Kd7 Ke2").unwrap();
+        assert_eq!(
+            broken.validate(&PuzzleOptions::default()),
+            Err(Flaw::NotUnique(2))
+        );
+        assert_eq!(
+            Puzzle::new("r3k3/8/8/3N4/8/8/P7/4K3 w - - 0 1", "d5d6"),
+            Err(BoardError::IllegalMove)
+        );
+
+        let motifs = |fen: &str, moves: &str| Puzzle::new(fen, moves).unwrap().motifs();
+        assert_eq!(
+            motifs("4k3/3n4/8/8/8/8/8/4KB2 w - - 0 1", "f1b5"),
+            vec![Motif::Pin]
+        );
+        assert_eq!(
+            motifs("3q4/8/3k4/8/R7/8/8/4K3 w - - 0 1", "a4d4"),
+            vec![Motif::Skewer]
+        );
+        assert_eq!(
+            motifs("4k3/8/8/8/4N3/8/8/4R1K1 w - - 0 1", "e4g5"),
+            vec![Motif::DiscoveredAttack]
+        );
+        assert_eq!(
+            motifs("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"),
+            vec![Motif::BackRankMate]
+        );
+        assert_eq!(
+            motifs("8/P5k1/8/8/8/8/8/K7 w - - 0 1", "a7a8q"),
+            vec![Motif::Promotion]
+        );
+        assert_eq!(
+            motifs("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"),
+            vec![Motif::EnPassant]
+        );
+    }
+
+    #[test]
+    fn parallel_search() {
+        use search::{search, SearchOptions};
+
+        fn shared<T: Send + Sync + Copy>() {}
+        shared::<Board>();
+
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1").unwrap();
+        let options = SearchOptions {
+            depth: 5,
+            threads: 4,
+            ..Default::default()
+        };
+        let result = search(&board, &options);
+        assert_eq!(result.best_move, Some(convert("d1d5")));
+        assert_eq!(result.depth, 5);
+
+        // A single thread searches the same tree every time
+        let options = SearchOptions {
+            depth: 4,
+            threads: 1,
+            ..options
+        };
+        let board =
+            Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")
+                .unwrap();
+        assert_eq!(search(&board, &options), search(&board, &options));
+    }
+
+    #[test]
+    fn perft_counts() {
+        use perft::*;
+
+        let options = PerftOptions::default();
+        let counts = [1, 20, 400, 8902, 197_281];
+        for (depth, &count) in counts.iter().enumerate() {
+            assert_eq!(perft(&Board::new(), depth as u32, &options), count);
+        }
+        let split = PerftOptions {
+            threads: 3,
+            split_second_ply: true,
+            hash: false,
+        };
+        assert_eq!(perft(&Board::new(), 4, &split), 197_281);
+
+        // Castling, en passant and promotions
+        let kiwipete =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        assert_eq!(perft(&kiwipete, 3, &options), 97_862);
+        let divided = divide(&kiwipete, 2, &options);
+        assert_eq!(divided.len(), 48);
+        assert_eq!(divided.iter().map(|(_, count)| count).sum::<u64>(), 2039);
+        let board = Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+        assert_eq!(perft(&board, 4, &options), 43_238);
+    }
+
+    // Takes minutes: cargo test --release -- --ignored perft_deep
+    #[test]
+    #[ignore]
+    fn perft_deep() {
+        use perft::*;
+
+        let options = PerftOptions::default();
+        assert_eq!(perft(&Board::new(), 5, &options), 4_865_609);
+        assert_eq!(perft(&Board::new(), 6, &options), 119_060_324);
+        assert_eq!(perft(&Board::new(), 7, &options), 3_195_901_860);
+    }
+
+    #[test]
+    fn skill_levels() {
+        use search::{search, SearchOptions, SkillLevel};
+        use std::collections::HashSet;
+
+        assert_eq!(SkillLevel::from_elo(500), SkillLevel::new(0));
+        assert_eq!(SkillLevel::from_elo(1460).level(), 7);
+        assert_eq!(SkillLevel::new(30), SkillLevel::MAX);
+        assert_eq!(SkillLevel::MAX.elo(), 2800);
+
+        let options = |level, seed| SearchOptions {
+            depth: 3,
+            skill: Some(SkillLevel::new(level)),
+            seed,
+            ..Default::default()
+        };
+        let board = Board::new();
+        let first_moves: HashSet<_> = (0..20)
+            .map(|seed| search(&board, &options(0, seed)).best_move)
+            .collect();
+        assert!(first_moves.len() > 3);
+        assert_eq!(
+            search(&board, &options(5, 1)),
+            search(&board, &options(5, 1))
+        );
+        assert_eq!(
+            search(&board, &options(20, 1)),
+            search(
+                &board,
+                &SearchOptions {
+                    depth: 3,
+                    ..Default::default()
+                }
+            )
+        );
+
+        // Even a weak player doesn't leave a queen hanging
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1").unwrap();
+        for seed in 0..10 {
+            assert_eq!(
+                search(&board, &options(8, seed)).best_move,
+                Some(convert("d1d5"))
+            );
+        }
+    }
+
+    #[test]
+    fn random_positions_and_games() {
+        use game::{random_game, Game};
+        use piece::PieceType::*;
+        use piece::{Color, Piece};
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        use square::Square;
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let board = Board::new();
+        let mv = board.random_legal_move(&mut rng).unwrap();
+        assert!(board.is_legal(&mv));
+        let mate = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert_eq!(mate.random_legal_move(&mut rng), None);
+
+        let game = random_game(&mut rng, 40);
+        assert!(game.moves().len() == 40 || game.is_over());
+        let mut replay = Game::new();
+        for mv in game.moves() {
+            replay.make_move(mv).unwrap();
+        }
+        assert_eq!(replay.board(), game.board());
+        assert_eq!(
+            random_game(&mut StdRng::seed_from_u64(9), 20).moves(),
+            random_game(&mut StdRng::seed_from_u64(9), 20).moves()
+        );
+
+        for _ in 0..50 {
+            let board =
+                Board::random_position(&mut rng, &[Queen, Pawn, Pawn], &[Rook, Knight]).unwrap();
+            assert_eq!(Board::from_fen(&board.to_fen()).unwrap(), board);
+            let count = |color, piece_type| {
+                Square::ALL
+                    .iter()
+                    .filter(|&&square| board.piece_at(square) == Some(Piece { piece_type, color }))
+                    .count()
+            };
+            assert_eq!(count(Color::White, Pawn), 2);
+            assert_eq!(count(Color::Black, Knight), 1);
+            assert_eq!(count(Color::Black, King), 1);
+        }
+        assert_eq!(
+            Board::random_position(&mut rng, &[King], &[]),
+            Err(BoardError::InvalidKingCount)
+        );
+        assert_eq!(
+            Board::random_position(&mut rng, &[Pawn; 40], &[Pawn; 10]),
+            Err(BoardError::InvalidMaterial)
+        );
+    }
+
+    #[test]
+    fn c_api() {
+        use ffi::*;
+        use std::ffi::CString;
+        use std::ptr;
+
+        unsafe {
+            let board = chess_board_new();
+            assert_eq!(chess_board_legal_moves(board, ptr::null_mut(), 0), 20);
+            let mut moves = [ChessMove {
+                from: 0,
+                to: 0,
+                promotion: 0,
+            }; 4];
+            assert_eq!(chess_board_legal_moves(board, moves.as_mut_ptr(), 4), 20);
+
+            let mut mv = moves[0];
+            let illegal = CString::new("e2e5").unwrap();
+            assert_eq!(
+                chess_board_parse_move(board, illegal.as_ptr(), &mut mv),
+                ChessError::IllegalMove
+            );
+            for san in ["f3", "e5", "g4", "Qh4#"] {
+                let san = CString::new(san).unwrap();
+                assert_eq!(
+                    chess_board_parse_move(board, san.as_ptr(), &mut mv),
+                    ChessError::Ok
+                );
+                assert_eq!(chess_board_make_move(board, mv), ChessError::Ok);
+            }
+            assert_eq!(chess_board_status(board), ChessStatus::Checkmate);
+            assert_eq!(chess_board_make_move(board, mv), ChessError::GameOver);
+
+            // Cut short like snprintf() but with the whole length returned
+            let fen = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3";
+            let mut buffer = [0 as std::os::raw::c_char; 8];
+            assert_eq!(chess_board_fen(board, buffer.as_mut_ptr(), 8), fen.len());
+            assert_eq!(
+                std::ffi::CStr::from_ptr(buffer.as_ptr()).to_str(),
+                Ok(&fen[..7])
+            );
+
+            let mut undone = mv;
+            assert!(chess_board_unmake_move(board, &mut undone));
+            assert_eq!(
+                (undone.from, undone.to),
+                (Square::D8 as u8, Square::H4 as u8)
+            );
+            assert_eq!(chess_board_turn(board), ChessColor::Black);
+            let mut san = [0 as std::os::raw::c_char; 16];
+            assert_eq!(chess_board_move_san(board, undone, san.as_mut_ptr(), 16), 4);
+            undone.to = 64;
+            assert_eq!(
+                chess_board_make_move(board, undone),
+                ChessError::InvalidSquare
+            );
+
+            let no_kings = CString::new("8/8/8/8/8/8/8/8 w - - 0 1").unwrap();
+            assert_eq!(
+                chess_board_load_fen(board, no_kings.as_ptr()),
+                ChessError::InvalidKingCount
+            );
+            assert_eq!(
+                chess_board_load_fen(ptr::null_mut(), no_kings.as_ptr()),
+                ChessError::NullPointer
+            );
+            let promotion = CString::new("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+            assert_eq!(
+                chess_board_load_fen(board, promotion.as_ptr()),
+                ChessError::Ok
+            );
+            let promote = ChessMove {
+                from: Square::B7 as u8,
+                to: Square::B8 as u8,
+                promotion: CHESS_PROMOTION_QUEEN,
+            };
+            assert_eq!(chess_board_make_move(board, promote), ChessError::Ok);
+            assert!(chess_board_unmake_move(board, ptr::null_mut()));
+            assert!(!chess_board_unmake_move(board, ptr::null_mut()));
+            chess_board_free(board);
+        }
+
+        // A panic becomes an error code instead of unwinding into C
+        assert_eq!(guard(ChessError::Ok, || ChessError::Ok), ChessError::Ok);
+        assert_eq!(
+            guard(ChessError::Internal, || -> ChessError { panic!("a bug") }),
+            ChessError::Internal
+        );
+    }
+
+    #[test]
+    fn only_pawns_promote() {
+        // The property tests found that is_legal() accepted these
+        let board = Board::new();
+        let knight = Move::new(Square::G1, Square::F3, Some(PieceType::Queen));
+        assert!(!board.is_legal(&knight));
+        assert_eq!(
+            board.illegal_move_reason(&knight),
+            Some(IllegalMoveReason::InvalidPromotion)
+        );
+        // A pawn that can't get there at all doesn't need a promotion either
+        let board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.illegal_move_reason(&Move::new(Square::A7, Square::H8, None)),
+            Some(IllegalMoveReason::CantMoveThere)
+        );
+        assert_eq!(
+            board.illegal_move_reason(&Move::new(Square::A7, Square::A8, None)),
+            Some(IllegalMoveReason::MissingPromotion)
+        );
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(!board.is_legal(&Move::new(Square::E1, Square::E2, Some(PieceType::Rook))));
+        assert!(board.is_legal(&Move::new(Square::E1, Square::E2, None)));
+    }
+
+    // Positions full of castling, en passant and promotions to start the random games from
+    const STARTS: [&str; 5] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    ];
+
+    proptest! {
+        // A fixed seed so that every run tries the same games
+        #![proptest_config(ProptestConfig {
+            cases: 32,
+            rng_seed: RngSeed::Fixed(0x5eed),
+            failure_persistence: None,
+            ..ProptestConfig::default()
+        })]
+
+        #[test]
+        fn random_games_keep_the_rules(start in 0..STARTS.len(), choices in vec(any::<u32>(), 0..120)) {
+            let mut game = Game::from_board(Board::from_fen(STARTS[start]).unwrap());
+            for choice in choices {
+                check_position(game.board());
+                let moves = game.board().generate_legal_moves();
+                if game.is_over() || moves.is_empty() {
+                    break;
+                }
+                game.make_move(&moves[choice as usize % moves.len()]).unwrap();
+            }
+        }
     }
 }
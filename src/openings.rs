@@ -0,0 +1,73 @@
+use board::{Board, Move};
+use game::Game;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+mod table;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Opening {
+    // Code of the Encyclopaedia of Chess Openings, e.g. "C65"
+    pub eco: &'static str,
+    pub name: &'static str,
+    // The moves in SAN with move numbers, e.g. "1. e4 e5 2. Nf3"
+    pub moves: &'static str,
+}
+
+// Polyglot key of every position in the table to its opening, built when it is first needed
+fn index() -> &'static HashMap<u64, Opening> {
+    static INDEX: OnceLock<HashMap<u64, Opening>> = OnceLock::new();
+    INDEX.get_or_init(|| {
+        let mut index = HashMap::new();
+        for &(eco, name, moves) in table::OPENINGS {
+            let game = Game::from_pgn(moves).expect("invalid moves in the opening table");
+            // Positions reached by more than one line keep the first name
+            index
+                .entry(game.board().polyglot_key())
+                .or_insert(Opening { eco, name, moves });
+        }
+        index
+    })
+}
+
+// The opening whose moves lead to exactly this position, no matter in which order they were played
+pub fn lookup(board: &Board) -> Option<Opening> {
+    index().get(&board.polyglot_key()).copied()
+}
+
+/*
+ * Names the opening of a game that started from the initial position. Every
+ * position on the way is looked up, so transpositions are recognized, and the
+ * last one that is a known opening wins. Moves after an illegal one are ignored.
+ */
+pub fn classify(moves: &[Move]) -> Option<Opening> {
+    let mut board = Board::new();
+    let mut opening = None;
+    for mv in moves {
+        if board.make_move(mv).is_err() {
+            break;
+        }
+        opening = lookup(&board).or(opening);
+    }
+    opening
+}
+
+impl Game {
+    // Like classify(), but also works for games that started from another position
+    pub fn opening(&self) -> Option<Opening> {
+        let mut board = *self.start_position();
+        let mut opening = lookup(&board);
+        for mv in self.moves() {
+            board.make_move(mv).unwrap();
+            opening = lookup(&board).or(opening);
+        }
+        opening
+    }
+}
+
+// Every opening of the table
+pub fn all() -> impl Iterator<Item = Opening> {
+    table::OPENINGS
+        .iter()
+        .map(|&(eco, name, moves)| Opening { eco, name, moves })
+}
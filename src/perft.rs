@@ -0,0 +1,156 @@
+/*
+ * Counts the positions reachable in a number of half moves, the usual check of
+ * a move generator. The moves at the root, or at the second ply, are shared out
+ * to a pool of threads, and positions reached by different move orders are
+ * only counted once thanks to a table shared by the threads.
+ */
+use board::{Board, Move};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+
+// Entries in the table, a power of two
+const ENTRIES: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PerftOptions {
+    // Threads counting at once
+    pub threads: usize,
+    // Shares out the positions after the second half move instead of the first, for many threads
+    pub split_second_ply: bool,
+    // Remembers the counts of positions that are reached more than once
+    pub hash: bool,
+}
+
+impl Default for PerftOptions {
+    fn default() -> Self {
+        Self {
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            split_second_ply: false,
+            hash: true,
+        }
+    }
+}
+
+// Counts by position and depth, with the key xor the count stored next to it to spot torn entries
+struct PerftTable {
+    slots: Vec<[AtomicU64; 2]>,
+}
+
+impl PerftTable {
+    fn new() -> Self {
+        Self {
+            slots: (0..ENTRIES)
+                .map(|_| [AtomicU64::new(0), AtomicU64::new(0)])
+                .collect(),
+        }
+    }
+
+    #[inline]
+    fn key(board: &Board, depth: u32) -> u64 {
+        board.polyglot_key() ^ (depth as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+    }
+
+    fn probe(&self, key: u64) -> Option<u64> {
+        let slot = &self.slots[key as usize & (ENTRIES - 1)];
+        let check = slot[0].load(Ordering::Relaxed);
+        let count = slot[1].load(Ordering::Relaxed);
+        match count != 0 && check ^ count == key {
+            true => Some(count),
+            false => None,
+        }
+    }
+
+    fn store(&self, key: u64, count: u64) {
+        let slot = &self.slots[key as usize & (ENTRIES - 1)];
+        slot[0].store(key ^ count, Ordering::Relaxed);
+        slot[1].store(count, Ordering::Relaxed);
+    }
+}
+
+fn count(board: &Board, depth: u32, table: Option<&PerftTable>) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = board.generate_legal_moves();
+    // Counting the moves is enough at the last ply
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let key = table.map(|_| PerftTable::key(board, depth));
+    if let Some(count) = table.zip(key).and_then(|(table, key)| table.probe(key)) {
+        return count;
+    }
+    let total = moves
+        .iter()
+        .map(|mv| {
+            let mut after = *board;
+            after.unsafe_make_move(mv).unwrap();
+            count(&after, depth - 1, table)
+        })
+        .sum();
+    if let Some((table, key)) = table.zip(key) {
+        table.store(key, total);
+    }
+    total
+}
+
+// The number of positions after each legal move, depth half moves from board
+pub fn divide(board: &Board, depth: u32, options: &PerftOptions) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    let moves = board.generate_legal_moves();
+
+    // The positions to count and the root move each of them belongs to
+    let mut jobs = Vec::new();
+    for (root, mv) in moves.iter().enumerate() {
+        let mut after = *board;
+        after.unsafe_make_move(mv).unwrap();
+        if options.split_second_ply && depth > 2 {
+            for reply in after.generate_legal_moves() {
+                let mut next = after;
+                next.unsafe_make_move(&reply).unwrap();
+                jobs.push((root, next, depth - 2));
+            }
+        } else {
+            jobs.push((root, after, depth - 1));
+        }
+    }
+
+    let table = match options.hash && depth > 2 {
+        true => Some(PerftTable::new()),
+        false => None,
+    };
+    let counts: Vec<AtomicU64> = moves.iter().map(|_| AtomicU64::new(0)).collect();
+    let next = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..options.threads.clamp(1, jobs.len().max(1)) {
+            scope.spawn(|| {
+                while let Some((root, board, depth)) =
+                    jobs.get(next.fetch_add(1, Ordering::Relaxed))
+                {
+                    let count = count(board, *depth, table.as_ref());
+                    counts[*root].fetch_add(count, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+
+    moves
+        .into_iter()
+        .zip(counts)
+        .map(|(mv, count)| (mv, count.into_inner()))
+        .collect()
+}
+
+// The number of positions depth half moves from board
+pub fn perft(board: &Board, depth: u32, options: &PerftOptions) -> u64 {
+    match depth {
+        0 => 1,
+        _ => divide(board, depth, options)
+            .iter()
+            .map(|(_, count)| count)
+            .sum(),
+    }
+}
@@ -1,4 +1,8 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum PieceType {
     Pawn,
     Rook,
@@ -9,6 +13,7 @@ pub enum PieceType {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Color {
     White,
     Black,
@@ -16,7 +21,7 @@ pub enum Color {
 
 impl Color {
     #[inline]
-    pub(crate) fn flip(self) -> Self {
+    pub fn flip(self) -> Self {
         match self {
             Color::White => Color::Black,
             Color::Black => Color::White,
@@ -25,7 +30,55 @@ impl Color {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Piece {
     pub piece_type: PieceType,
     pub color: Color,
 }
+
+impl PieceType {
+    // The uppercase letter used by FEN and SAN, e.g. 'N' for a knight
+    pub fn to_char(self) -> char {
+        match self {
+            PieceType::Pawn => 'P',
+            PieceType::Rook => 'R',
+            PieceType::Knight => 'N',
+            PieceType::Bishop => 'B',
+            PieceType::Queen => 'Q',
+            PieceType::King => 'K',
+        }
+    }
+
+    // Accepts both upper- and lowercase letters
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'P' => Some(PieceType::Pawn),
+            'R' => Some(PieceType::Rook),
+            'N' => Some(PieceType::Knight),
+            'B' => Some(PieceType::Bishop),
+            'Q' => Some(PieceType::Queen),
+            'K' => Some(PieceType::King),
+            _ => None,
+        }
+    }
+}
+
+impl Piece {
+    // The FEN letter, uppercase for white and lowercase for black
+    pub fn to_char(self) -> char {
+        match self.color {
+            Color::White => self.piece_type.to_char(),
+            Color::Black => self.piece_type.to_char().to_ascii_lowercase(),
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        Some(Piece {
+            piece_type: PieceType::from_char(c)?,
+            color: match c.is_ascii_uppercase() {
+                true => Color::White,
+                false => Color::Black,
+            },
+        })
+    }
+}
@@ -0,0 +1,253 @@
+/*
+ * Tactics puzzles: a position and the moves that solve it, starting with a
+ * move of the solver and alternating with the opponent's replies.
+ */
+use board::{Board, BoardError, Move, Position};
+use piece::{Color, PieceType};
+use search::{mate_in, piece_value, search, SearchOptions};
+
+// Tactical patterns found in the moves of the solver
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Motif {
+    // A piece attacking two of the opponent's pieces at once
+    Fork,
+    // A piece pinning an opponent's piece to its king
+    Pin,
+    // A line piece attacking a piece that has to move away from a lesser piece behind it
+    Skewer,
+    // A piece moving out of the way of an attack by another
+    DiscoveredAttack,
+    // A rook or queen mating a king that its own pawns keep on the back rank
+    BackRankMate,
+    Promotion,
+    EnPassant,
+}
+
+// Why a puzzle doesn't have a single solution, with the index of the move in the solution
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Flaw {
+    // Another move of the solver is about as good
+    NotUnique(usize),
+    // The opponent has a clearly better reply
+    NotBest(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleOptions {
+    // Depth of the search that scores every move, in plies
+    pub depth: u32,
+    // Centipawns the move of the solver has to be ahead of any other
+    pub margin: i32,
+}
+
+impl Default for PuzzleOptions {
+    fn default() -> Self {
+        Self {
+            depth: 3,
+            margin: 200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub board: Board,
+    pub solution: Vec<Move>,
+}
+
+// The score of mv for the player making it
+fn score(board: &Board, mv: &Move, depth: u32) -> i32 {
+    let mut after = *board;
+    after.unsafe_make_move(mv).unwrap();
+    let options = SearchOptions {
+        depth: depth.saturating_sub(1),
+        ..SearchOptions::default()
+    };
+    -search(&after, &options).score
+}
+
+impl Puzzle {
+    // Reads the moves, separated by spaces in UCI or SAN, and checks that each is legal
+    pub fn new(fen: &str, moves: &str) -> Result<Puzzle, BoardError> {
+        let start = Board::from_fen(fen)?;
+        let mut board = start;
+        let mut solution = Vec::new();
+        for notation in moves.split_whitespace() {
+            let mv = board.parse_move(notation)?;
+            board.make_move(&mv)?;
+            solution.push(mv);
+        }
+        Ok(Puzzle {
+            board: start,
+            solution,
+        })
+    }
+
+    /*
+     * Searches every position of the solution to check that each move of the
+     * solver is better than any other by the margin, and that the opponent
+     * doesn't have a reply better by the margin. Any move that mates is accepted.
+     */
+    pub fn validate(&self, options: &PuzzleOptions) -> Result<(), Flaw> {
+        let mut board = self.board;
+        for (i, mv) in self.solution.iter().enumerate() {
+            let mut after = board;
+            after.make_move(mv).unwrap();
+            let moves = board.generate_legal_moves();
+            if moves.len() > 1 && !after.is_checkmate() {
+                let own = score(&board, mv, options.depth);
+                let best = moves
+                    .iter()
+                    .filter(|&other| other != mv)
+                    .map(|other| score(&board, other, options.depth))
+                    .max()
+                    .unwrap();
+                if i % 2 == 0 {
+                    // A slower mate is no alternative to a mate
+                    let unique = match mate_in(own) {
+                        Some(moves) if moves > 0 => best < own,
+                        _ => best <= own - options.margin,
+                    };
+                    if !unique {
+                        return Err(Flaw::NotUnique(i));
+                    }
+                } else if best > own + options.margin {
+                    return Err(Flaw::NotBest(i));
+                }
+            }
+            board = after;
+        }
+        Ok(())
+    }
+
+    // The motifs of the moves of the solver, each listed once
+    pub fn motifs(&self) -> Vec<Motif> {
+        let mut motifs = Vec::new();
+        let mut board = self.board;
+        for (i, mv) in self.solution.iter().enumerate() {
+            let mut after = board;
+            after.make_move(mv).unwrap();
+            if i % 2 == 0 {
+                motifs.extend(move_motifs(&board, &after, mv));
+            }
+            board = after;
+        }
+        motifs.sort();
+        motifs.dedup();
+        motifs
+    }
+}
+
+// What a piece is worth when it comes to tactics, where the king counts most
+fn worth(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::King => i32::MAX,
+        piece_type => piece_value(piece_type),
+    }
+}
+
+fn move_motifs(before: &Board, after: &Board, mv: &Move) -> Vec<Motif> {
+    let mut motifs = Vec::new();
+    let piece = after.piece_at(mv.to).unwrap();
+    let (color, opponent) = (piece.color, piece.color.flip());
+
+    if mv.promotion.is_some() {
+        motifs.push(Motif::Promotion);
+    }
+    if piece.piece_type == PieceType::Pawn
+        && mv.from.col != mv.to.col
+        && before.piece_at(mv.to).is_none()
+    {
+        motifs.push(Motif::EnPassant);
+    }
+
+    // Attacking the king, a more valuable piece or one that isn't defended
+    let targets = (0..8)
+        .flat_map(|row| (0..8).map(move |col| Position { row, col }))
+        .filter(|&pos| {
+            after.piece_at(pos).is_some_and(|target| {
+                target.color == opponent
+                    && target.piece_type != PieceType::Pawn
+                    && after.attackers_of(pos, color).contains(&mv.to)
+                    && (worth(target.piece_type) > worth(piece.piece_type)
+                        || after.defenders_of(pos).is_empty())
+            })
+        })
+        .count();
+    if targets >= 2 {
+        motifs.push(Motif::Fork);
+    }
+
+    if after
+        .pinned_pieces(opponent)
+        .iter()
+        .any(|&(_, pinner)| pinner == mv.to)
+    {
+        motifs.push(Motif::Pin);
+    }
+
+    for (dir, types) in Board::line_dirs() {
+        if !types.contains(&piece.piece_type) {
+            continue;
+        }
+        if let (Some(front), Some(behind)) = after.first_two_pieces(&mv.to, dir) {
+            let (front, behind) = (
+                after.piece_at(front).unwrap(),
+                after.piece_at(behind).unwrap(),
+            );
+            if front.color == opponent
+                && behind.color == opponent
+                && behind.piece_type != PieceType::Pawn
+                && worth(front.piece_type) > worth(behind.piece_type)
+            {
+                motifs.push(Motif::Skewer);
+                break;
+            }
+        }
+    }
+
+    if before.discovered_attackers(color).iter().any(|attack| {
+        attack.blocker == mv.from
+            && after
+                .attackers_of(attack.target, color)
+                .contains(&attack.attacker)
+            && after
+                .piece_at(attack.target)
+                .is_some_and(|target| target.piece_type != PieceType::Pawn)
+    }) {
+        motifs.push(Motif::DiscoveredAttack);
+    }
+
+    if after.is_checkmate() {
+        let king = after.king_pos(opponent);
+        let (back_rank, forward) = match opponent {
+            Color::White => (7, -1),
+            Color::Black => (0, 1),
+        };
+        let boxed_in = (-1..=1)
+            .map(|dc| Position {
+                row: back_rank + forward,
+                col: king.col + dc,
+            })
+            .filter(|pos| !pos.out_of_bounds())
+            .all(|pos| {
+                after
+                    .piece_at(pos)
+                    .is_some_and(|piece| piece.color == opponent)
+            });
+        let checkers = after.checkers();
+        if king.row == back_rank
+            && boxed_in
+            && checkers.iter().all(|&checker| {
+                checker.row == back_rank
+                    && matches!(
+                        after.piece_at(checker).unwrap().piece_type,
+                        PieceType::Rook | PieceType::Queen
+                    )
+            })
+        {
+            motifs.push(Motif::BackRankMate);
+        }
+    }
+    motifs
+}
@@ -0,0 +1,10 @@
+/*
+ * Drawing boards for humans. text() renders to a string for terminals and
+ * logs, which is also what Board's Display implementation uses. svg() draws
+ * diagrams for web pages and articles.
+ */
+pub mod svg;
+pub mod text;
+
+pub use self::svg::*;
+pub use self::text::*;
@@ -0,0 +1,350 @@
+use board::{Board, Move, Position, BOARD_COL_COUNT, BOARD_ROW_COUNT};
+use piece::{Color, PieceType};
+use std::fmt::Write;
+
+// An arrow from the center of one square to another, e.g. to show a plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arrow {
+    pub from: Position,
+    pub to: Position,
+    // Any SVG color, e.g. "#15781b" or "red"
+    pub color: String,
+}
+
+impl Arrow {
+    // A green arrow, accepts Squares as well as Positions
+    pub fn new<F: Into<Position>, T: Into<Position>>(from: F, to: T) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            color: DEFAULT_ARROW_COLOR.to_string(),
+        }
+    }
+}
+
+// A square filled with a translucent color, drawn below the pieces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+    pub pos: Position,
+    pub color: String,
+}
+
+impl Highlight {
+    // A green highlight, accepts Squares as well as Positions
+    pub fn new<P: Into<Position>>(pos: P) -> Self {
+        Self {
+            pos: pos.into(),
+            color: DEFAULT_HIGHLIGHT_COLOR.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SvgOptions {
+    // Width and height of the board itself in pixels, coordinates add a border around it
+    pub size: u32,
+    // Ranks and files written on the border
+    pub coordinates: bool,
+    // The color at the bottom of the board
+    pub orientation: Color,
+    pub last_move: Option<Move>,
+    // Marks the king of the player to move with a red glow if it is in check
+    pub highlight_check: bool,
+    pub highlights: Vec<Highlight>,
+    pub arrows: Vec<Arrow>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            size: 360,
+            coordinates: true,
+            orientation: Color::White,
+            last_move: None,
+            highlight_check: true,
+            highlights: Vec::new(),
+            arrows: Vec::new(),
+        }
+    }
+}
+
+const LIGHT_SQUARE_COLOR: &str = "#f0d9b5";
+const DARK_SQUARE_COLOR: &str = "#b58863";
+const BORDER_COLOR: &str = "#404040";
+const COORDINATE_COLOR: &str = "#e0e0e0";
+const LAST_MOVE_COLOR: &str = "#cdd26a";
+const DEFAULT_HIGHLIGHT_COLOR: &str = "#15781b";
+const DEFAULT_ARROW_COLOR: &str = "#15781b";
+
+// Every piece is drawn in a 45 by 45 box and scaled to the size of a square
+const PIECE_SIZE: f64 = 45.0;
+
+fn piece_shape(piece_type: PieceType) -> &'static str {
+    match piece_type {
+        PieceType::Pawn => {
+            "<circle cx=\"22.5\" cy=\"15\" r=\"5.5\"/>\
+             <path d=\"M15 35 L30 35 L27.5 26 Q22.5 20 17.5 26 Z\"/>\
+             <path d=\"M12 39 L33 39 L33 35 L12 35 Z\"/>"
+        }
+        PieceType::Rook => {
+            "<path d=\"M12 39 L33 39 L33 35 L30 35 L29 20 L32 17 L32 10 L28 10 L28 13 \
+             L25 13 L25 10 L20 10 L20 13 L17 13 L17 10 L13 10 L13 17 L16 20 L15 35 L12 35 Z\"/>"
+        }
+        PieceType::Knight => {
+            "<path d=\"M14 39 L34 39 L33 30 Q33 14 22 9 L20 5 L18 10 Q12 14 10 23 \
+             L12 26 L16 23 L21 21 Q17 27 14 31 Z\"/>\
+             <circle cx=\"17\" cy=\"15\" r=\"1.2\" class=\"detail\"/>"
+        }
+        PieceType::Bishop => {
+            "<circle cx=\"22.5\" cy=\"8.5\" r=\"2.5\"/>\
+             <path d=\"M16 34 L29 34 Q31 22 22.5 11.5 Q14 22 16 34 Z\"/>\
+             <path d=\"M12 39 L33 39 L33 35 L12 35 Z\"/>"
+        }
+        PieceType::Queen => {
+            "<path d=\"M11 39 L34 39 L33 33 L36 14 L30 26 L28 11 L24.5 25 L22.5 9 \
+             L20.5 25 L17 11 L15 26 L9 14 L12 33 Z\"/>\
+             <circle cx=\"9\" cy=\"13\" r=\"2\"/><circle cx=\"17\" cy=\"10\" r=\"2\"/>\
+             <circle cx=\"22.5\" cy=\"8\" r=\"2\"/><circle cx=\"28\" cy=\"10\" r=\"2\"/>\
+             <circle cx=\"36\" cy=\"13\" r=\"2\"/>"
+        }
+        PieceType::King => {
+            "<path d=\"M21 4 h3 v3 h3 v3 h-3 v6 h-3 v-6 h-3 v-3 h3 Z\"/>\
+             <path d=\"M11 39 L34 39 L33 32 Q39 22 30 18 Q24 18 22.5 24 Q21 18 15 18 \
+             Q6 22 12 32 Z\"/>"
+        }
+    }
+}
+
+fn piece_id(color: Color, piece_type: PieceType) -> String {
+    let color = match color {
+        Color::White => "white",
+        Color::Black => "black",
+    };
+    format!("{}-{:?}", color, piece_type).to_lowercase()
+}
+
+fn defs() -> String {
+    let mut defs = String::from("<defs>");
+    for color in [Color::White, Color::Black] {
+        let (fill, stroke) = match color {
+            Color::White => ("#ffffff", "#000000"),
+            Color::Black => ("#000000", "#ffffff"),
+        };
+        for piece_type in [
+            PieceType::Pawn,
+            PieceType::Rook,
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Queen,
+            PieceType::King,
+        ] {
+            let _ = write!(
+                defs,
+                "<g id=\"{}\" fill=\"{}\" stroke=\"{}\" stroke-width=\"1.5\" \
+                 stroke-linejoin=\"round\">{}</g>",
+                piece_id(color, piece_type),
+                fill,
+                stroke,
+                piece_shape(piece_type)
+                    .replace("class=\"detail\"", &format!("fill=\"{}\"", stroke))
+            );
+        }
+    }
+    defs.push_str(
+        "<radialGradient id=\"check\"><stop offset=\"0%\" stop-color=\"#ff0000\" stop-opacity=\"1\"/>\
+         <stop offset=\"50%\" stop-color=\"#e70000\" stop-opacity=\"1\"/>\
+         <stop offset=\"100%\" stop-color=\"#9e0000\" stop-opacity=\"0\"/></radialGradient>",
+    );
+    defs.push_str("</defs>");
+    defs
+}
+
+/*
+ * Draws the board as a standalone SVG document. The pieces are embedded in the
+ * document, so it doesn't depend on any other files or fonts apart from the
+ * coordinates, which use the default sans-serif font.
+ */
+pub fn svg(board: &Board, options: &SvgOptions) -> String {
+    let square_size = options.size as f64 / BOARD_COL_COUNT as f64;
+    let margin = match options.coordinates {
+        true => square_size / 2.0,
+        false => 0.0,
+    };
+    let total_size = options.size as f64 + 2.0 * margin;
+
+    // The top left corner of the square at pos
+    let corner = |pos: Position| -> (f64, f64) {
+        let (row, col) = match options.orientation {
+            Color::White => (pos.row, pos.col),
+            Color::Black => (
+                BOARD_ROW_COUNT as i8 - 1 - pos.row,
+                BOARD_COL_COUNT as i8 - 1 - pos.col,
+            ),
+        };
+        (
+            margin + col as f64 * square_size,
+            margin + row as f64 * square_size,
+        )
+    };
+    let center = |pos: Position| {
+        let (x, y) = corner(pos);
+        (x + square_size / 2.0, y + square_size / 2.0)
+    };
+    let rect = |out: &mut String, pos: Position, fill: &str, opacity: f64| {
+        let (x, y) = corner(pos);
+        let _ = write!(
+            out,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" fill-opacity=\"{}\"/>",
+            x, y, square_size, square_size, fill, opacity
+        );
+    };
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+         version=\"1.1\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">",
+        total_size
+    );
+    out.push_str(&defs());
+
+    if options.coordinates {
+        let _ = write!(
+            out,
+            "<rect x=\"0\" y=\"0\" width=\"{0}\" height=\"{0}\" fill=\"{1}\"/>",
+            total_size, BORDER_COLOR
+        );
+    }
+
+    let squares = board.get_board();
+    let mut positions = Vec::new();
+    for row in 0..BOARD_ROW_COUNT {
+        for col in 0..BOARD_COL_COUNT {
+            positions.push(Position {
+                row: row as i8,
+                col: col as i8,
+            });
+        }
+    }
+
+    for pos in positions.iter() {
+        let color = match (pos.row + pos.col) % 2 {
+            0 => LIGHT_SQUARE_COLOR,
+            _ => DARK_SQUARE_COLOR,
+        };
+        rect(&mut out, *pos, color, 1.0);
+    }
+
+    if let Some(mv) = options.last_move {
+        for pos in [mv.from, mv.to] {
+            if !pos.out_of_bounds() {
+                rect(&mut out, pos, LAST_MOVE_COLOR, 0.8);
+            }
+        }
+    }
+    for highlight in options.highlights.iter() {
+        if !highlight.pos.out_of_bounds() {
+            rect(&mut out, highlight.pos, &highlight.color, 0.5);
+        }
+    }
+    if options.highlight_check && board.is_in_check() {
+        rect(
+            &mut out,
+            board.king_pos(board.whose_turn()),
+            "url(#check)",
+            1.0,
+        );
+    }
+
+    if options.coordinates {
+        let font_size = margin * 0.6;
+        for i in 0..BOARD_ROW_COUNT {
+            let pos = Position {
+                row: i as i8,
+                col: i as i8,
+            };
+            let (x, y) = center(pos);
+            let _ = write!(
+                out,
+                "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" fill=\"{}\" \
+                 text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\
+                 <text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" fill=\"{}\" \
+                 text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                margin / 2.0,
+                y,
+                font_size,
+                COORDINATE_COLOR,
+                BOARD_ROW_COUNT - i,
+                x,
+                total_size - margin / 2.0,
+                font_size,
+                COORDINATE_COLOR,
+                (b'a' + i as u8) as char
+            );
+        }
+    }
+
+    let scale = square_size / PIECE_SIZE;
+    for pos in positions.iter() {
+        if let Some(piece) = squares[*pos] {
+            let (x, y) = corner(*pos);
+            let _ = write!(
+                out,
+                "<use xlink:href=\"#{}\" transform=\"translate({}, {}) scale({})\"/>",
+                piece_id(piece.color, piece.piece_type),
+                x,
+                y,
+                scale
+            );
+        }
+    }
+
+    for arrow in options.arrows.iter() {
+        if arrow.from.out_of_bounds() || arrow.to.out_of_bounds() || arrow.from == arrow.to {
+            continue;
+        }
+        out.push_str(&arrow_svg(
+            center(arrow.from),
+            center(arrow.to),
+            square_size,
+            &arrow.color,
+        ));
+    }
+
+    out.push_str("</svg>");
+    out
+}
+
+// A line ending in a triangle whose tip touches the center of the target square
+fn arrow_svg(from: (f64, f64), to: (f64, f64), square_size: f64, color: &str) -> String {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    let (ux, uy) = (dx / length, dy / length);
+
+    let width = square_size * 0.15;
+    let head_length = square_size * 0.4;
+    let head_width = square_size * 0.4;
+
+    // Where the shaft ends and the head starts
+    let base = (to.0 - ux * head_length, to.1 - uy * head_length);
+    let (px, py) = (-uy * head_width / 2.0, ux * head_width / 2.0);
+
+    format!(
+        "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\" \
+         stroke-linecap=\"round\" opacity=\"0.8\"/>\
+         <polygon points=\"{},{} {},{} {},{}\" fill=\"{}\" opacity=\"0.8\"/>",
+        from.0,
+        from.1,
+        base.0,
+        base.1,
+        color,
+        width,
+        to.0,
+        to.1,
+        base.0 + px,
+        base.1 + py,
+        base.0 - px,
+        base.1 - py,
+        color
+    )
+}
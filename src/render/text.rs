@@ -0,0 +1,162 @@
+use board::{Board, Move, Position, BOARD_COL_COUNT, BOARD_ROW_COUNT};
+use piece::{Color, Piece, PieceType};
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Glyphs {
+    // Letters as in FEN, uppercase for white
+    Ascii,
+    // The chess symbols of Unicode, e.g. ♔ and ♚
+    Unicode,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextOptions {
+    pub glyphs: Glyphs,
+    // Ranks to the left of and files below the board
+    pub coordinates: bool,
+    // The color at the bottom of the board
+    pub orientation: Color,
+    // Marked with [ ] around the squares, or a yellow background with ANSI colors
+    pub last_move: Option<Move>,
+    // Marks the king of the player to move with ( ) or a red background if in check
+    pub highlight_check: bool,
+    // Light and dark square backgrounds using ANSI escape codes
+    pub ansi_colors: bool,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self {
+            glyphs: Glyphs::Ascii,
+            coordinates: true,
+            orientation: Color::White,
+            last_move: None,
+            highlight_check: true,
+            ansi_colors: false,
+        }
+    }
+}
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_LIGHT_SQUARE: &str = "\x1b[48;5;180m";
+const ANSI_DARK_SQUARE: &str = "\x1b[48;5;137m";
+const ANSI_LAST_MOVE: &str = "\x1b[48;5;185m";
+const ANSI_CHECK: &str = "\x1b[48;5;167m";
+const ANSI_WHITE_PIECE: &str = "\x1b[1;97m";
+const ANSI_BLACK_PIECE: &str = "\x1b[1;30m";
+
+fn glyph(square: Option<Piece>, glyphs: Glyphs) -> char {
+    let piece = match square {
+        Some(piece) => piece,
+        None => {
+            return match glyphs {
+                Glyphs::Ascii => '.',
+                Glyphs::Unicode => '·',
+            }
+        }
+    };
+    match glyphs {
+        Glyphs::Ascii => piece.to_char(),
+        Glyphs::Unicode => match (piece.color, piece.piece_type) {
+            (Color::White, PieceType::King) => '♔',
+            (Color::White, PieceType::Queen) => '♕',
+            (Color::White, PieceType::Rook) => '♖',
+            (Color::White, PieceType::Bishop) => '♗',
+            (Color::White, PieceType::Knight) => '♘',
+            (Color::White, PieceType::Pawn) => '♙',
+            (Color::Black, PieceType::King) => '♚',
+            (Color::Black, PieceType::Queen) => '♛',
+            (Color::Black, PieceType::Rook) => '♜',
+            (Color::Black, PieceType::Bishop) => '♝',
+            (Color::Black, PieceType::Knight) => '♞',
+            (Color::Black, PieceType::Pawn) => '♟',
+        },
+    }
+}
+
+// Draws the board with one line per rank and three characters per square
+pub fn text(board: &Board, options: &TextOptions) -> String {
+    let squares = board.get_board();
+    let check = match options.highlight_check && board.is_in_check() {
+        true => Some(board.king_pos(board.whose_turn())),
+        false => None,
+    };
+    let is_last_move = |pos: Position| {
+        options
+            .last_move
+            .is_some_and(|mv| mv.from == pos || mv.to == pos)
+    };
+
+    // Row 0 is the eighth rank, which is at the top when White is at the bottom
+    let flip = |i: usize, count: usize| match options.orientation {
+        Color::White => i,
+        Color::Black => count - 1 - i,
+    };
+
+    let mut out = String::new();
+    for i in 0..BOARD_ROW_COUNT {
+        let row = flip(i, BOARD_ROW_COUNT);
+        if options.coordinates {
+            out.push_str(&format!("{} ", BOARD_ROW_COUNT - row));
+        }
+        for j in 0..BOARD_COL_COUNT {
+            let col = flip(j, BOARD_COL_COUNT);
+            let pos = Position {
+                row: row as i8,
+                col: col as i8,
+            };
+            let square = squares[pos];
+            let glyph = glyph(square, options.glyphs);
+
+            if options.ansi_colors {
+                let background = if check == Some(pos) {
+                    ANSI_CHECK
+                } else if is_last_move(pos) {
+                    ANSI_LAST_MOVE
+                } else if (row + col) % 2 == 0 {
+                    ANSI_LIGHT_SQUARE
+                } else {
+                    ANSI_DARK_SQUARE
+                };
+                let foreground = match square.map(|piece| piece.color) {
+                    Some(Color::Black) => ANSI_BLACK_PIECE,
+                    _ => ANSI_WHITE_PIECE,
+                };
+                out.push_str(&format!("{}{} {} ", background, foreground, glyph));
+            } else {
+                let (open, close) = if check == Some(pos) {
+                    ('(', ')')
+                } else if is_last_move(pos) {
+                    ('[', ']')
+                } else {
+                    (' ', ' ')
+                };
+                out.push(open);
+                out.push(glyph);
+                out.push(close);
+            }
+        }
+        if options.ansi_colors {
+            out.push_str(ANSI_RESET);
+        }
+        out.push('\n');
+    }
+
+    if options.coordinates {
+        out.push_str("  ");
+        for j in 0..BOARD_COL_COUNT {
+            let col = flip(j, BOARD_COL_COUNT);
+            out.push_str(&format!(" {} ", (b'a' + col as u8) as char));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+impl fmt::Display for Board {
+    // Uses the default TextOptions, call render::text() for anything else
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&text(self, &TextOptions::default()))
+    }
+}
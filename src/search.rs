@@ -0,0 +1,375 @@
+/*
+ * A small alpha-beta engine: iterative deepening negamax with a quiescence
+ * search over captures, ordering moves by the previous principal variation and
+ * by most valuable victim / least valuable attacker. Scores are in centipawns
+ * from the point of view of the player to move.
+ *
+ * More threads search the same position at once (Lazy SMP), sharing what they
+ * find through a transposition table. Only the first thread's result is used,
+ * the others fill the table with positions it is about to need.
+ */
+pub mod eval;
+mod skill;
+mod tt;
+
+pub use self::eval::*;
+pub use self::skill::SkillLevel;
+use self::tt::{Bound, Entry, TranspositionTable};
+
+use board::{Board, Move, FIFTY_MOVE_RULE_HALFMOVES};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// The score of a checkmate, minus the number of plies it takes to get there
+pub const MATE_SCORE: i32 = 100_000;
+const INFINITY: i32 = 1_000_000;
+// Any score closer than this to MATE_SCORE is a forced mate
+const MAX_MATE_PLIES: i32 = 1_000;
+// How many nodes to search between looking at the clock
+const NODES_BETWEEN_TIME_CHECKS: u64 = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchOptions {
+    // Maximum depth in plies, not counting the quiescence search
+    pub depth: u32,
+    // Stops after this long and returns the result of the deepest finished iteration
+    pub time_limit: Option<Duration>,
+    // Search threads, a single thread gives the same result every time
+    pub threads: usize,
+    /*
+     * Plays weaker below SkillLevel::MAX, always with a single thread and
+     * without a time limit as the search is shallow. The same seed gives the
+     * same moves, so vary it from game to game.
+     */
+    pub skill: Option<SkillLevel>,
+    pub seed: u64,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            depth: 4,
+            time_limit: None,
+            threads: 1,
+            skill: None,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    // None if the player to move has no legal moves
+    pub best_move: Option<Move>,
+    pub score: i32,
+    // The depth of the deepest finished iteration
+    pub depth: u32,
+    pub nodes: u64,
+    // The expected continuation, starting with best_move
+    pub pv: Vec<Move>,
+}
+
+impl SearchResult {
+    // Moves until mate, positive if the player to move mates and negative if it gets mated
+    pub fn mate_in(&self) -> Option<i32> {
+        mate_in(self.score)
+    }
+}
+
+pub(crate) fn mate_in(score: i32) -> Option<i32> {
+    if score > MATE_SCORE - MAX_MATE_PLIES {
+        Some((MATE_SCORE - score + 1) / 2)
+    } else if score < -MATE_SCORE + MAX_MATE_PLIES {
+        Some(-(MATE_SCORE + score) / 2)
+    } else {
+        None
+    }
+}
+
+// Finds the best move for the player to move
+pub fn search(board: &Board, options: &SearchOptions) -> SearchResult {
+    let table = TranspositionTable::new();
+    let stop = AtomicBool::new(false);
+    let deadline = options.time_limit.map(|limit| Instant::now() + limit);
+    let skill = options.skill.filter(|&skill| skill < SkillLevel::MAX);
+    let searcher = || Searcher {
+        nodes: 0,
+        deadline,
+        stopped: false,
+        table: &table,
+        stop: &stop,
+        skill,
+        seed: options.seed,
+    };
+    if let Some(skill) = skill {
+        return Searcher {
+            deadline: None,
+            ..searcher()
+        }
+        .choose(board, options.depth.min(skill.depth()).max(1), skill);
+    }
+
+    thread::scope(|scope| {
+        let helpers: Vec<_> = (1..options.threads.max(1))
+            .map(|id| {
+                let mut helper = searcher();
+                // Half of the helpers search one ply deeper so that the threads don't all do the same work
+                let depth = options.depth.max(1) + (id % 2) as u32;
+                scope.spawn(move || {
+                    helper.iterate(board, depth);
+                    helper.nodes
+                })
+            })
+            .collect();
+        let mut main = searcher();
+        let mut result = main.iterate(board, options.depth.max(1));
+        stop.store(true, Ordering::Relaxed);
+        result.nodes = main.nodes
+            + helpers
+                .into_iter()
+                .map(|helper| helper.join().unwrap())
+                .sum::<u64>();
+        result
+    })
+}
+
+struct Searcher<'a> {
+    nodes: u64,
+    deadline: Option<Instant>,
+    stopped: bool,
+    table: &'a TranspositionTable,
+    // Set once the first thread is done, so that the helpers stop as well
+    stop: &'a AtomicBool,
+    skill: Option<SkillLevel>,
+    seed: u64,
+}
+
+// Mate scores are stored as seen from the position, not from the root
+#[inline]
+fn to_table(score: i32, ply: i32) -> i32 {
+    match mate_in(score) {
+        Some(_) if score > 0 => score + ply,
+        Some(_) => score - ply,
+        None => score,
+    }
+}
+
+#[inline]
+fn from_table(score: i32, ply: i32) -> i32 {
+    match mate_in(score) {
+        Some(_) if score > 0 => score - ply,
+        Some(_) => score + ply,
+        None => score,
+    }
+}
+
+impl<'a> Searcher<'a> {
+    fn iterate(&mut self, board: &Board, max_depth: u32) -> SearchResult {
+        let mut result = SearchResult {
+            best_move: None,
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            pv: Vec::new(),
+        };
+
+        for depth in 1..=max_depth {
+            let (score, pv) = self.negamax(board, depth, 0, -INFINITY, INFINITY, &result.pv);
+            // The first iteration always finishes so that there is a move to return
+            if self.stopped && depth > 1 {
+                break;
+            }
+            result.best_move = pv.first().copied();
+            result.score = score;
+            result.depth = depth;
+            result.pv = pv;
+            // Searching deeper won't find a faster mate
+            if mate_in(score).is_some() {
+                break;
+            }
+        }
+        result
+    }
+
+    // Scores every move and picks one the way a player of skill would
+    fn choose(&mut self, board: &Board, depth: u32, skill: SkillLevel) -> SearchResult {
+        let moves = board.generate_legal_moves();
+        if moves.is_empty() {
+            return SearchResult {
+                nodes: self.nodes,
+                ..self.iterate(board, 1)
+            };
+        }
+        let mut lines: Vec<(i32, Vec<Move>)> = moves
+            .into_iter()
+            .map(|mv| {
+                let mut after = *board;
+                after.unsafe_make_move(&mv).unwrap();
+                let (score, pv) = self.negamax(&after, depth - 1, 1, -INFINITY, INFINITY, &[]);
+                let mut line = vec![mv];
+                line.extend(pv);
+                (-score, line)
+            })
+            .collect();
+        let scores: Vec<i32> = lines.iter().map(|(score, _)| *score).collect();
+        let (score, pv) = lines.swap_remove(skill.choose(board, self.seed, &scores));
+        SearchResult {
+            best_move: pv.first().copied(),
+            score,
+            depth,
+            nodes: self.nodes,
+            pv,
+        }
+    }
+
+    fn out_of_time(&mut self) -> bool {
+        if !self.stopped && self.nodes.is_multiple_of(NODES_BETWEEN_TIME_CHECKS) {
+            self.stopped = self.stop.load(Ordering::Relaxed)
+                || self
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline);
+        }
+        self.stopped
+    }
+
+    // pv_hint is the principal variation of the previous iteration, searched first
+    fn negamax(
+        &mut self,
+        board: &Board,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+        pv_hint: &[Move],
+    ) -> (i32, Vec<Move>) {
+        self.nodes += 1;
+        // The first iteration has to find a move no matter how long it takes
+        if depth > 1 && self.out_of_time() {
+            return (0, Vec::new());
+        }
+
+        let mut moves = board.generate_legal_moves();
+        if moves.is_empty() {
+            return match board.is_in_check() {
+                true => (-MATE_SCORE + ply, Vec::new()),
+                false => (0, Vec::new()),
+            };
+        }
+        if board.halfmove_clock() >= FIFTY_MOVE_RULE_HALFMOVES || board.is_insufficient_material() {
+            return (0, Vec::new());
+        }
+        if depth == 0 {
+            return (self.quiescence(board, alpha, beta), Vec::new());
+        }
+
+        let key = board.polyglot_key();
+        let entry = self.table.probe(board, key);
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.depth >= depth) {
+            let score = from_table(entry.score, ply);
+            let cutoff = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+            if cutoff {
+                return (score, Vec::new());
+            }
+        }
+
+        let original_alpha = alpha;
+        let hint = pv_hint
+            .first()
+            .copied()
+            .or(entry.and_then(|entry| entry.best_move));
+        order_moves(board, &mut moves, hint.as_ref());
+
+        let mut best_score = -INFINITY;
+        let mut best_pv = Vec::new();
+        for mv in moves {
+            let mut child = *board;
+            child.unsafe_make_move(&mv).unwrap();
+            let child_hint = match pv_hint.first() == Some(&mv) {
+                true => &pv_hint[1..],
+                false => &[],
+            };
+            let (score, child_pv) =
+                self.negamax(&child, depth - 1, ply + 1, -beta, -alpha, child_hint);
+            let score = -score;
+            if self.stopped && depth > 1 {
+                return (0, Vec::new());
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_pv = vec![mv];
+                best_pv.extend(child_pv);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score <= original_alpha {
+            Bound::Upper
+        } else {
+            Bound::Exact
+        };
+        self.table.store(
+            board,
+            key,
+            Entry {
+                score: to_table(best_score, ply),
+                depth,
+                bound,
+                best_move: best_pv.first().copied(),
+            },
+        );
+        (best_score, best_pv)
+    }
+
+    // Only looks at captures so that the evaluation isn't done in the middle of an exchange
+    fn quiescence(&mut self, board: &Board, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        let stand_pat =
+            evaluate(board) + self.skill.map_or(0, |skill| skill.error(board, self.seed));
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let mut captures = board.generate_legal_capturing_moves();
+        order_moves(board, &mut captures, None);
+        for mv in captures {
+            let mut child = *board;
+            child.unsafe_make_move(&mv).unwrap();
+            let score = -self.quiescence(&child, -beta, -alpha);
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
+
+// Puts the hinted move first, then captures of valuable pieces by cheap ones, then promotions
+pub(crate) fn order_moves(board: &Board, moves: &mut [Move], first: Option<&Move>) {
+    moves.sort_by_cached_key(|mv| {
+        if Some(mv) == first {
+            return i32::MIN;
+        }
+        let mut key = 0;
+        if let Some(victim) = board.piece_at(mv.to) {
+            let attacker = board.piece_at(mv.from).unwrap();
+            key -= 10 * piece_value(victim.piece_type) - piece_value(attacker.piece_type);
+        }
+        if let Some(promotion) = mv.promotion {
+            key -= piece_value(promotion);
+        }
+        key
+    });
+}
@@ -0,0 +1,163 @@
+use board::{Board, Position, BOARD_COL_COUNT, BOARD_ROW_COUNT};
+use piece::{Color, PieceType};
+
+/*
+ * A simple evaluation counting material and rewarding pieces for standing on
+ * good squares, using the piece-square tables of Tomasz Michniewski's
+ * "Simplified Evaluation Function". The tables are written from White's side
+ * with the eighth rank first, which is the same order as Board uses for rows.
+ */
+
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const KING_MIDDLEGAME_TABLE: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+#[rustfmt::skip]
+const KING_ENDGAME_TABLE: [i32; 64] = [
+    -50,-40,-30,-20,-20,-30,-40,-50,
+    -30,-20,-10,  0,  0,-10,-20,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-30,  0,  0,  0,  0,-30,-30,
+    -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+// Material left, not counting pawns and kings, below which the kings should become active
+const ENDGAME_MATERIAL: i32 = 1300;
+
+#[inline]
+pub fn piece_value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Pawn => 100,
+        PieceType::Knight => 320,
+        PieceType::Bishop => 330,
+        PieceType::Rook => 500,
+        PieceType::Queen => 900,
+        PieceType::King => 0,
+    }
+}
+
+// The score in centipawns from the point of view of the player to move
+pub fn evaluate(board: &Board) -> i32 {
+    let squares = board.get_board();
+
+    let mut non_pawn_material = 0;
+    for row in squares.iter() {
+        for piece in row.iter().flatten() {
+            if piece.piece_type != PieceType::Pawn {
+                non_pawn_material += piece_value(piece.piece_type);
+            }
+        }
+    }
+    let king_table = match non_pawn_material <= ENDGAME_MATERIAL {
+        true => &KING_ENDGAME_TABLE,
+        false => &KING_MIDDLEGAME_TABLE,
+    };
+
+    let mut score = 0;
+    for row in 0..BOARD_ROW_COUNT {
+        for col in 0..BOARD_COL_COUNT {
+            let pos = Position {
+                row: row as i8,
+                col: col as i8,
+            };
+            let piece = match squares[pos] {
+                Some(piece) => piece,
+                None => continue,
+            };
+            // Black's pieces look the tables up from their own side of the board
+            let index = match piece.color {
+                Color::White => row * BOARD_COL_COUNT + col,
+                Color::Black => (BOARD_ROW_COUNT - 1 - row) * BOARD_COL_COUNT + col,
+            };
+            let table = match piece.piece_type {
+                PieceType::Pawn => &PAWN_TABLE,
+                PieceType::Knight => &KNIGHT_TABLE,
+                PieceType::Bishop => &BISHOP_TABLE,
+                PieceType::Rook => &ROOK_TABLE,
+                PieceType::Queen => &QUEEN_TABLE,
+                PieceType::King => king_table,
+            };
+            let value = piece_value(piece.piece_type) + table[index];
+            score += match piece.color {
+                Color::White => value,
+                Color::Black => -value,
+            };
+        }
+    }
+
+    match board.whose_turn() {
+        Color::White => score,
+        Color::Black => -score,
+    }
+}
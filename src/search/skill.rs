@@ -0,0 +1,80 @@
+use board::Board;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// Elo of the weakest level, every level above it adds ELO_PER_LEVEL
+const BASE_ELO: u32 = 800;
+const ELO_PER_LEVEL: u32 = 100;
+
+/*
+ * How well the engine plays, from 0 for a beginner to 20 for full strength.
+ * Lower levels search less deep, misjudge positions by a random amount and now
+ * and then play a move that isn't the best, the worse the move the less often.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SkillLevel(u8);
+
+impl SkillLevel {
+    pub const MAX: SkillLevel = SkillLevel(20);
+
+    // Levels above 20 are full strength
+    #[inline]
+    pub fn new(level: u8) -> SkillLevel {
+        SkillLevel(level.min(Self::MAX.0))
+    }
+
+    // The level closest to a rough Elo rating, from 800 at level 0 to 2800 at level 20
+    pub fn from_elo(elo: u32) -> SkillLevel {
+        let level = (elo.saturating_sub(BASE_ELO) + ELO_PER_LEVEL / 2) / ELO_PER_LEVEL;
+        SkillLevel::new(level.min(u8::MAX as u32) as u8)
+    }
+
+    #[inline]
+    pub fn level(self) -> u8 {
+        self.0
+    }
+
+    #[inline]
+    pub fn elo(self) -> u32 {
+        BASE_ELO + ELO_PER_LEVEL * self.0 as u32
+    }
+
+    // The deepest the engine looks at this level
+    pub(super) fn depth(self) -> u32 {
+        1 + self.0 as u32 / 4
+    }
+
+    // The most centipawns the evaluation is off by
+    fn noise(self) -> i32 {
+        (Self::MAX.0 - self.0) as i32 * 10
+    }
+
+    // How many centipawns worse a move is when it gets played e times less often than the best
+    fn temperature(self) -> f64 {
+        (Self::MAX.0 - self.0) as f64 * 8.0
+    }
+
+    /*
+     * The error in the evaluation of board. It only depends on the position and
+     * the seed, so the engine misjudges a position the same way every time.
+     */
+    pub(super) fn error(self, board: &Board, seed: u64) -> i32 {
+        let noise = self.noise();
+        match noise {
+            0 => 0,
+            _ => StdRng::seed_from_u64(seed ^ board.polyglot_key()).gen_range(-noise..=noise),
+        }
+    }
+
+    // Picks one of the scored moves, the better the score the likelier
+    pub(super) fn choose(self, board: &Board, seed: u64, scores: &[i32]) -> usize {
+        let best = *scores.iter().max().unwrap();
+        let temperature = self.temperature().max(1.0);
+        let weights = scores
+            .iter()
+            .map(|&score| (-((best - score) as f64) / temperature).exp());
+        let mut rng = StdRng::seed_from_u64(seed.rotate_left(32) ^ board.polyglot_key());
+        WeightedIndex::new(weights).unwrap().sample(&mut rng)
+    }
+}
@@ -0,0 +1,88 @@
+use board::{Board, Move};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Entries in the table, a power of two
+const ENTRIES: usize = 1 << 18;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) enum Bound {
+    Exact,
+    // The score is at least this, the search failed high
+    Lower,
+    // The score is at most this, no move raised alpha
+    Upper,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) struct Entry {
+    pub(super) score: i32,
+    pub(super) depth: u32,
+    pub(super) bound: Bound,
+    pub(super) best_move: Option<Move>,
+}
+
+/*
+ * A transposition table shared by the search threads without locking. Each
+ * slot holds the data and the key xor the data, so an entry that is torn by two
+ * threads writing at once no longer matches its key and is ignored.
+ */
+pub(super) struct TranspositionTable {
+    slots: Vec<[AtomicU64; 2]>,
+}
+
+impl TranspositionTable {
+    pub(super) fn new() -> Self {
+        Self {
+            slots: (0..ENTRIES)
+                .map(|_| [AtomicU64::new(0), AtomicU64::new(0)])
+                .collect(),
+        }
+    }
+
+    #[inline]
+    fn slot(&self, key: u64) -> &[AtomicU64; 2] {
+        &self.slots[key as usize & (ENTRIES - 1)]
+    }
+
+    pub(super) fn probe(&self, board: &Board, key: u64) -> Option<Entry> {
+        let slot = self.slot(key);
+        let check = slot[0].load(Ordering::Relaxed);
+        let data = slot[1].load(Ordering::Relaxed);
+        if data == 0 || check ^ data != key {
+            return None;
+        }
+
+        // Score in the low 32 bits, then depth, bound and move
+        let bound = match (data >> 40) & 3 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        };
+        let raw_move = (data >> 42) as u16;
+        Some(Entry {
+            score: data as u32 as i32,
+            depth: ((data >> 32) & 0xff) as u32,
+            bound,
+            best_move: match raw_move {
+                0 => None,
+                raw_move => Some(board.move_from_polyglot(raw_move)),
+            },
+        })
+    }
+
+    pub(super) fn store(&self, board: &Board, key: u64, entry: Entry) {
+        let bound = match entry.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+        let raw_move = entry.best_move.map_or(0, |mv| board.move_to_polyglot(&mv)) as u64;
+        let data = entry.score as u32 as u64
+            | (entry.depth.min(0xff) as u64) << 32
+            | bound << 40
+            | raw_move << 42;
+        let slot = self.slot(key);
+        slot[0].store(key ^ data, Ordering::Relaxed);
+        slot[1].store(data, Ordering::Relaxed);
+    }
+}
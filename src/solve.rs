@@ -0,0 +1,177 @@
+/*
+ * Proves forced mates in composed problems. A problem is sound when exactly one
+ * first move, the key, mates in n moves against every defence; other first
+ * moves that do so as well are cooks.
+ */
+mod proof_number;
+
+use board::{Board, Move};
+use search::order_moves;
+use std::collections::HashMap;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Method {
+    // Depth first over every move, checks first
+    #[default]
+    Exhaustive,
+    // Grows the tree where a proof or refutation is closest, good for deep mates with few checks
+    ProofNumber,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    // The shortest mate against the longest defence, starting with a key
+    pub line: Vec<Move>,
+    // Every first move that mates in n moves
+    pub keys: Vec<Move>,
+}
+
+impl Solution {
+    // Whether the problem has a single key and no cooks
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        self.keys.len() == 1
+    }
+}
+
+// The legal moves with the positions after them, checks first
+fn candidates(board: &Board) -> Vec<(Move, Board)> {
+    let mut moves = board.generate_legal_moves();
+    order_moves(board, &mut moves, None);
+    let mut candidates: Vec<(Move, Board)> = moves
+        .into_iter()
+        .map(|mv| {
+            let mut after = *board;
+            after.unsafe_make_move(&mv).unwrap();
+            (mv, after)
+        })
+        .collect();
+    candidates.sort_by_key(|(_, after)| !after.is_in_check());
+    candidates
+}
+
+#[derive(Default)]
+struct Prover {
+    // How the line is proven, the exhaustive search keeps its results in known
+    method: Method,
+    // Whether the player to move mates in the number of moves
+    known: HashMap<(u64, u32), bool>,
+}
+
+impl Prover {
+    // Whether the player to move mates in at most n moves
+    fn mates(&mut self, board: &Board, n: u32) -> bool {
+        if n == 0 {
+            return false;
+        }
+        let key = (board.polyglot_key(), n);
+        if let Some(&mates) = self.known.get(&key) {
+            return mates;
+        }
+        let mut mates = false;
+        for (_, after) in candidates(board) {
+            // Only a check can mate right away, and the checks come first
+            if n == 1 && !after.is_in_check() {
+                break;
+            }
+            if self.forced(&after, n - 1) {
+                mates = true;
+                break;
+            }
+        }
+        self.known.insert(key, mates);
+        mates
+    }
+
+    // Whether the player to move is mated now or after every reply within n moves of the opponent
+    fn forced(&mut self, board: &Board, n: u32) -> bool {
+        let mut moves = board.generate_legal_moves();
+        if moves.is_empty() {
+            return board.is_in_check();
+        }
+        if n == 0 {
+            return false;
+        }
+        order_moves(board, &mut moves, None);
+        moves.iter().all(|mv| {
+            let mut after = *board;
+            after.unsafe_make_move(mv).unwrap();
+            self.mates(&after, n)
+        })
+    }
+
+    // forced() by the method of the prover
+    fn proves(&mut self, board: &Board, n: u32) -> bool {
+        match self.method {
+            Method::Exhaustive => self.forced(board, n),
+            Method::ProofNumber => proof_number::forced(board, n),
+        }
+    }
+
+    // The fewest moves the player to move needs to mate, if it is at most n
+    fn distance(&mut self, board: &Board, n: u32) -> Option<u32> {
+        (1..=n).find(|&moves| match self.method {
+            Method::Exhaustive => self.mates(board, moves),
+            Method::ProofNumber => candidates(board)
+                .iter()
+                .any(|(_, after)| self.proves(after, moves - 1)),
+        })
+    }
+
+    // The mating line from a position where the player to move mates in n moves
+    fn line(&mut self, board: &Board, n: u32) -> Vec<Move> {
+        let mut line = Vec::new();
+        let mut board = *board;
+        while let Some(n) = self.distance(&board, n) {
+            let (mv, after) = candidates(&board)
+                .into_iter()
+                .find(|(_, after)| self.proves(after, n - 1))
+                .unwrap();
+            line.push(mv);
+            board = after;
+            if board.is_checkmate() {
+                break;
+            }
+            // The defence that puts off the mate the longest
+            let (reply, after) = candidates(&board)
+                .into_iter()
+                .max_by_key(|(_, after)| self.distance(after, n - 1))
+                .unwrap();
+            line.push(reply);
+            board = after;
+        }
+        line
+    }
+}
+
+// The line of a forced mate in at most n moves for the player to move, None if there is none
+pub fn mate_in(board: &Board, n: u32) -> Option<Vec<Move>> {
+    let mut prover = Prover::default();
+    match prover.mates(board, n) {
+        true => Some(prover.line(board, n)),
+        false => None,
+    }
+}
+
+// Every key of a mate in at most n moves and the line of the shortest one, None if there is no mate
+pub fn solve(board: &Board, n: u32, method: Method) -> Option<Solution> {
+    if n == 0 {
+        return None;
+    }
+    let mut prover = Prover {
+        method,
+        ..Prover::default()
+    };
+    let keys: Vec<Move> = candidates(board)
+        .into_iter()
+        .filter(|(_, after)| prover.proves(after, n - 1))
+        .map(|(mv, _)| mv)
+        .collect();
+    match keys.is_empty() {
+        true => None,
+        false => Some(Solution {
+            line: prover.line(board, n),
+            keys,
+        }),
+    }
+}
@@ -0,0 +1,123 @@
+use board::Board;
+use solve::candidates;
+
+const INFINITY: u32 = u32::MAX;
+
+struct Node {
+    board: Board,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    // The player that tries to mate is to move
+    attacker: bool,
+    // Moves of the attacker left, counting the one about to be played
+    moves_left: u32,
+    // How many leaves at least have to be proven to prove or disprove the mate
+    proof: u32,
+    disproof: u32,
+}
+
+impl Node {
+    fn new(board: Board, parent: Option<usize>, attacker: bool, moves_left: u32) -> Node {
+        let mut node = Node {
+            board,
+            parent,
+            children: Vec::new(),
+            attacker,
+            moves_left,
+            proof: 1,
+            disproof: 1,
+        };
+        let proven = if !node.board.has_legal_moves() {
+            Some(!attacker && node.board.is_in_check())
+        } else if moves_left == 0 {
+            Some(false)
+        } else {
+            None
+        };
+        match proven {
+            Some(true) => (node.proof, node.disproof) = (0, INFINITY),
+            Some(false) => (node.proof, node.disproof) = (INFINITY, 0),
+            None => {}
+        }
+        node
+    }
+}
+
+struct Tree {
+    nodes: Vec<Node>,
+}
+
+impl Tree {
+    // The unresolved leaf that settles the most with the least work
+    fn most_proving(&self) -> usize {
+        let mut index = 0;
+        while !self.nodes[index].children.is_empty() {
+            let node = &self.nodes[index];
+            let children = node.children.iter().copied();
+            index = match node.attacker {
+                true => children.min_by_key(|&child| self.nodes[child].proof),
+                false => children.min_by_key(|&child| self.nodes[child].disproof),
+            }
+            .unwrap();
+        }
+        index
+    }
+
+    fn expand(&mut self, index: usize) {
+        let (attacker, moves_left) = (self.nodes[index].attacker, self.nodes[index].moves_left);
+        let moves_left = match attacker {
+            true => moves_left - 1,
+            false => moves_left,
+        };
+        for (_, after) in candidates(&self.nodes[index].board) {
+            let child = self.nodes.len();
+            self.nodes
+                .push(Node::new(after, Some(index), !attacker, moves_left));
+            self.nodes[index].children.push(child);
+        }
+    }
+
+    fn update(&mut self, mut index: usize) {
+        loop {
+            let node = &self.nodes[index];
+            let proofs = node.children.iter().map(|&child| self.nodes[child].proof);
+            let disproofs = node
+                .children
+                .iter()
+                .map(|&child| self.nodes[child].disproof);
+            let (proof, disproof) = match node.attacker {
+                true => (
+                    proofs.min().unwrap(),
+                    disproofs.fold(0, u32::saturating_add),
+                ),
+                false => (
+                    proofs.fold(0, u32::saturating_add),
+                    disproofs.min().unwrap(),
+                ),
+            };
+            let node = &mut self.nodes[index];
+            node.proof = proof;
+            node.disproof = disproof;
+            match node.parent {
+                Some(parent) => index = parent,
+                None => break,
+            }
+        }
+    }
+}
+
+/*
+ * Whether the player to move is mated now or after every reply within n moves
+ * of the opponent, found by proof-number search.
+ */
+pub(super) fn forced(board: &Board, n: u32) -> bool {
+    let mut tree = Tree {
+        nodes: vec![Node::new(*board, None, false, n)],
+    };
+    while tree.nodes[0].proof != 0 && tree.nodes[0].disproof != 0 {
+        let leaf = tree.most_proving();
+        tree.expand(leaf);
+        tree.update(leaf);
+    }
+    tree.nodes[0].proof == 0
+}
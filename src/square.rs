@@ -0,0 +1,164 @@
+use board::{BoardError, Position, BOARD_COL_COUNT, BOARD_ROW_COUNT};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/*
+ * A square on the chessboard in the usual algebraic notation.
+ * Unlike Position this can never be out of bounds, and rank() counts
+ * from White's side of the board, i.e. A1 is White's queenside rook.
+ */
+#[rustfmt::skip]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Square {
+    A1, B1, C1, D1, E1, F1, G1, H1,
+    A2, B2, C2, D2, E2, F2, G2, H2,
+    A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4,
+    A5, B5, C5, D5, E5, F5, G5, H5,
+    A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7,
+    A8, B8, C8, D8, E8, F8, G8, H8,
+}
+
+impl Square {
+    #[rustfmt::skip]
+    pub const ALL: [Square; 64] = {
+        use self::Square::*;
+        [
+            A1, B1, C1, D1, E1, F1, G1, H1,
+            A2, B2, C2, D2, E2, F2, G2, H2,
+            A3, B3, C3, D3, E3, F3, G3, H3,
+            A4, B4, C4, D4, E4, F4, G4, H4,
+            A5, B5, C5, D5, E5, F5, G5, H5,
+            A6, B6, C6, D6, E6, F6, G6, H6,
+            A7, B7, C7, D7, E7, F7, G7, H7,
+            A8, B8, C8, D8, E8, F8, G8, H8,
+        ]
+    };
+
+    // file = 0 is the A file, rank = 0 is the first rank
+    #[inline]
+    pub fn new(file: i8, rank: i8) -> Option<Self> {
+        if !(0..BOARD_COL_COUNT as i8).contains(&file)
+            || !(0..BOARD_ROW_COUNT as i8).contains(&rank)
+        {
+            return None;
+        }
+        Some(Self::ALL[(rank * 8 + file) as usize])
+    }
+
+    // A1 = 0, B1 = 1, ..., H8 = 63
+    #[inline]
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    #[inline]
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    // 0 for the A file up to 7 for the H file
+    #[inline]
+    pub fn file(self) -> i8 {
+        self as i8 % 8
+    }
+
+    // 0 for the first rank up to 7 for the eighth rank
+    #[inline]
+    pub fn rank(self) -> i8 {
+        self as i8 / 8
+    }
+
+    // Parses squares written like "e4", returns None if it isn't a valid square
+    pub fn from_algebraic(s: &str) -> Option<Self> {
+        let mut chars = s.chars();
+        let (file, rank) = match (chars.next(), chars.next(), chars.next()) {
+            (Some(file), Some(rank), None) => (file.to_ascii_lowercase(), rank),
+            _ => return None,
+        };
+        if !('a'..='h').contains(&file) || !('1'..='8').contains(&rank) {
+            return None;
+        }
+        Self::new(file as i8 - 'a' as i8, rank as i8 - '1' as i8)
+    }
+
+    // The square df files to the right and dr ranks up, seen from White's side
+    #[inline]
+    pub fn offset(self, df: i8, dr: i8) -> Option<Self> {
+        Self::new(self.file() + df, self.rank() + dr)
+    }
+
+    // Towards the eighth rank
+    #[inline]
+    pub fn up(self) -> Option<Self> {
+        self.offset(0, 1)
+    }
+
+    // Towards the first rank
+    #[inline]
+    pub fn down(self) -> Option<Self> {
+        self.offset(0, -1)
+    }
+
+    // Towards the A file
+    #[inline]
+    pub fn left(self) -> Option<Self> {
+        self.offset(-1, 0)
+    }
+
+    // Towards the H file
+    #[inline]
+    pub fn right(self) -> Option<Self> {
+        self.offset(1, 0)
+    }
+
+    #[inline]
+    pub fn to_position(self) -> Position {
+        Position::from(self)
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            (b'a' + self.file() as u8) as char,
+            (b'1' + self.rank() as u8) as char
+        )
+    }
+}
+
+impl FromStr for Square {
+    type Err = BoardError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_algebraic(s).ok_or(BoardError::InvalidSquare)
+    }
+}
+
+impl From<Square> for Position {
+    #[inline]
+    fn from(square: Square) -> Self {
+        Position {
+            row: BOARD_ROW_COUNT as i8 - 1 - square.rank(),
+            col: square.file(),
+        }
+    }
+}
+
+impl<'a> From<&'a Square> for Position {
+    #[inline]
+    fn from(square: &'a Square) -> Self {
+        Position::from(*square)
+    }
+}
+
+impl TryFrom<Position> for Square {
+    type Error = BoardError;
+    #[inline]
+    fn try_from(pos: Position) -> Result<Self, Self::Error> {
+        pos.to_square().ok_or(BoardError::InvalidSquare)
+    }
+}
@@ -0,0 +1,397 @@
+use board::{Board, Move};
+use piece::{Color, PieceType};
+use square::Square;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+mod table;
+use self::table::{Dtz, Table};
+
+// The outcome of a position for the player to move, taking the fifty move rule into account
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    // Lost, but the opponent can't mate before the fifty move rule ends the game
+    BlessedLoss,
+    Draw,
+    // Won, but not before the fifty move rule ends the game
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    fn from_value(value: i32) -> Wdl {
+        match value {
+            -2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            0 => Wdl::Draw,
+            1 => Wdl::CursedWin,
+            _ => Wdl::Win,
+        }
+    }
+}
+
+// The DTZ a WDL value stands for when the next move resets the fifty move counter
+const WDL_TO_DTZ: [i32; 5] = [-1, -101, 0, 101, 1];
+
+// A table file that is only read when it is needed for the first time
+#[derive(Debug)]
+struct Entry {
+    name: String,
+    path: PathBuf,
+    dtz: bool,
+    table: OnceLock<Option<Table>>,
+}
+
+impl Entry {
+    fn table(&self) -> Option<&Table> {
+        self.table
+            .get_or_init(|| {
+                let data = fs::read(&self.path).ok()?;
+                Table::new(&self.name, data, self.dtz).ok()
+            })
+            .as_ref()
+    }
+}
+
+/*
+ * Probes Syzygy tablebases, the .rtbw files for win, draw or loss and the
+ * .rtbz files for the distance to zeroing the fifty move counter (DTZ).
+ * Files are read when they are first needed and kept in memory.
+ */
+#[derive(Debug, Default)]
+pub struct Syzygy {
+    // By material, e.g. "KRvKB", with the pieces of White first
+    wdl: HashMap<String, Entry>,
+    dtz: HashMap<String, Entry>,
+    max_pieces: usize,
+}
+
+// The pieces of color as written in table names, e.g. "KRB"
+fn side(board: &Board, color: Color) -> String {
+    let mut side = String::new();
+    for piece_type in [
+        PieceType::King,
+        PieceType::Queen,
+        PieceType::Rook,
+        PieceType::Bishop,
+        PieceType::Knight,
+        PieceType::Pawn,
+    ] {
+        for &square in Square::ALL.iter() {
+            if board
+                .piece_at(square)
+                .is_some_and(|piece| piece.color == color && piece.piece_type == piece_type)
+            {
+                side.push(piece_type.to_char());
+            }
+        }
+    }
+    side
+}
+
+#[inline]
+fn is_capture(board: &Board, mv: &Move) -> bool {
+    board.piece_at(mv.to).is_some()
+}
+
+#[inline]
+fn is_pawn_move(board: &Board, mv: &Move) -> bool {
+    board.piece_at(mv.from).map(|piece| piece.piece_type) == Some(PieceType::Pawn)
+}
+
+#[inline]
+fn is_en_passant(board: &Board, mv: &Move) -> bool {
+    is_pawn_move(board, mv) && mv.from.col != mv.to.col && !is_capture(board, mv)
+}
+
+fn after(board: &Board, mv: &Move) -> Board {
+    let mut after = *board;
+    after.make_move(mv).unwrap();
+    after
+}
+
+impl Syzygy {
+    // Finds the tables in dir, a file that turns out to be broken is treated as missing
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<Syzygy> {
+        let mut syzygy = Syzygy::default();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let dtz = match path.extension().and_then(|extension| extension.to_str()) {
+                Some("rtbw") => false,
+                Some("rtbz") => true,
+                _ => continue,
+            };
+            let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(name) if name.contains('v') && name.chars().all(|c| "KQRBNPv".contains(c)) => {
+                    name.to_string()
+                }
+                _ => continue,
+            };
+            syzygy.max_pieces = syzygy.max_pieces.max(name.len() - 1);
+            let entries = if dtz {
+                &mut syzygy.dtz
+            } else {
+                &mut syzygy.wdl
+            };
+            entries.insert(
+                name.clone(),
+                Entry {
+                    name,
+                    path,
+                    dtz,
+                    table: OnceLock::new(),
+                },
+            );
+        }
+        Ok(syzygy)
+    }
+
+    // The most pieces, kings included, of any table found
+    #[inline]
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    // The table for the material of the board and its name as seen from the board
+    fn table<'a>(
+        &self,
+        entries: &'a HashMap<String, Entry>,
+        board: &Board,
+    ) -> Option<(&'a Table, String)> {
+        let white = side(board, Color::White);
+        let black = side(board, Color::Black);
+        let key = format!("{}v{}", white, black);
+        let entry = entries
+            .get(&key)
+            .or_else(|| entries.get(&format!("{}v{}", black, white)))?;
+        Some((entry.table()?, key))
+    }
+
+    fn probe_wdl_table(&self, board: &Board) -> Option<i32> {
+        if side(board, Color::White) == "K" && side(board, Color::Black) == "K" {
+            return Some(0);
+        }
+        let (table, key) = self.table(&self.wdl, board)?;
+        table.probe_wdl(board, &key)
+    }
+
+    /*
+     * The WDL value with captures searched, since tables may store anything for
+     * positions where a capture is best. The second value is 2 if a capture wins,
+     * 1 + (alpha > 0) if alpha can't be improved upon and 1 otherwise.
+     */
+    fn probe_ab(&self, board: &Board, mut alpha: i32, beta: i32) -> Option<(i32, u8)> {
+        for mv in board.generate_legal_moves() {
+            if !is_capture(board, &mv) {
+                continue;
+            }
+            let v = -self.probe_ab(&after(board, &mv), -beta, -alpha)?.0;
+            if v > alpha {
+                if v >= beta {
+                    return Some((v, 2));
+                }
+                alpha = v;
+            }
+        }
+
+        let v = self.probe_wdl_table(board)?;
+        match alpha >= v {
+            true => Some((alpha, 1 + (alpha > 0) as u8)),
+            false => Some((v, 1)),
+        }
+    }
+
+    fn can_probe(&self, board: &Board) -> bool {
+        let pieces = Square::ALL
+            .iter()
+            .filter(|&&square| board.piece_at(square).is_some())
+            .count();
+        pieces <= self.max_pieces
+            && board.castling_rights(Color::White) == (false, false)
+            && board.castling_rights(Color::Black) == (false, false)
+    }
+
+    fn wdl(&self, board: &Board) -> Option<i32> {
+        let mut v = self.probe_ab(board, -2, 2)?.0;
+        if board.en_passant_square().is_none() {
+            return Some(v);
+        }
+
+        // Tables don't know about en passant
+        let moves = board.generate_legal_moves();
+        let mut v1 = -3;
+        for mv in moves.iter().filter(|mv| is_en_passant(board, mv)) {
+            v1 = v1.max(-self.probe_ab(&after(board, mv), -2, 2)?.0);
+        }
+        if v1 > -3 {
+            if v1 >= v {
+                v = v1;
+            } else if v == 0 && moves.iter().all(|mv| is_en_passant(board, mv)) {
+                // The losing en passant capture is the only move
+                v = v1;
+            }
+        }
+        Some(v)
+    }
+
+    // None if the material has no table or castling is still possible
+    pub fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        match self.can_probe(board) {
+            true => self.wdl(board).map(Wdl::from_value),
+            false => None,
+        }
+    }
+
+    fn dtz_no_en_passant(&self, board: &Board) -> Option<i32> {
+        let (wdl, found) = self.probe_ab(board, -2, 2)?;
+        if wdl == 0 {
+            return Some(0);
+        }
+        // A capture wins
+        if found == 2 {
+            return Some(if wdl == 2 { 1 } else { 101 });
+        }
+
+        let moves = board.generate_legal_moves();
+        if wdl > 0 {
+            for mv in moves.iter() {
+                if !is_pawn_move(board, mv) || is_capture(board, mv) {
+                    continue;
+                }
+                let v = -self.probe_ab(&after(board, mv), -2, -wdl + 1)?.0;
+                if v == wdl {
+                    return Some(if v == 2 { 1 } else { 101 });
+                }
+            }
+        }
+
+        let (table, key) = self.table(&self.dtz, board)?;
+        if let Dtz::Found(value) = table.probe_dtz(board, &key, wdl)? {
+            let mut dtz = 1 + value;
+            if wdl & 1 != 0 {
+                dtz += 100;
+            }
+            return Some(if wdl >= 0 { dtz } else { -dtz });
+        }
+
+        // The table only has the other side to move, so look one move ahead
+        if wdl > 0 {
+            let mut best = 0xffff;
+            for mv in moves.iter() {
+                if is_capture(board, mv) || is_pawn_move(board, mv) {
+                    continue;
+                }
+                let v = -self.dtz(&after(board, mv))?;
+                if v > 0 && v + 1 < best {
+                    best = v + 1;
+                }
+            }
+            Some(best)
+        } else {
+            let mut best = -1;
+            for mv in moves.iter() {
+                let after = after(board, mv);
+                let v = if is_capture(board, mv) || is_pawn_move(board, mv) {
+                    match wdl == -2 {
+                        true => -1,
+                        false => match self.probe_ab(&after, 1, 2)?.0 {
+                            2 => 0,
+                            _ => -101,
+                        },
+                    }
+                } else {
+                    -self.dtz(&after)? - 1
+                };
+                best = best.min(v);
+            }
+            Some(best)
+        }
+    }
+
+    fn dtz(&self, board: &Board) -> Option<i32> {
+        let mut v = self.dtz_no_en_passant(board)?;
+        if board.en_passant_square().is_none() {
+            return Some(v);
+        }
+
+        let moves = board.generate_legal_moves();
+        let mut v1 = -3;
+        for mv in moves.iter().filter(|mv| is_en_passant(board, mv)) {
+            v1 = v1.max(-self.probe_ab(&after(board, mv), -2, 2)?.0);
+        }
+        if v1 > -3 {
+            let v1 = WDL_TO_DTZ[(v1 + 2) as usize];
+            if v < -100 {
+                if v1 >= 0 {
+                    v = v1;
+                }
+            } else if v < 0 {
+                if !(-100..0).contains(&v1) {
+                    v = v1;
+                }
+            } else if v > 100 {
+                if v1 > 0 {
+                    v = v1;
+                }
+            } else if v > 0 {
+                if v1 == 1 {
+                    v = v1;
+                }
+            } else if v1 >= 0 || moves.iter().all(|mv| is_en_passant(board, mv)) {
+                v = v1;
+            }
+        }
+        Some(v)
+    }
+
+    /*
+     * Half moves until the fifty move counter is reset by a capture or pawn move
+     * that keeps the result, positive when winning and negative when losing. Values
+     * above 100 are cursed wins or blessed losses. 0 for draws.
+     */
+    pub fn probe_dtz(&self, board: &Board) -> Option<i32> {
+        match self.can_probe(board) {
+            true => self.dtz(board),
+            false => None,
+        }
+    }
+
+    /*
+     * The move that keeps the best result: the quickest way to a reset of the
+     * fifty move counter when winning, and the slowest when losing.
+     */
+    pub fn best_move(&self, board: &Board) -> Option<Move> {
+        let dtz = self.probe_dtz(board)?;
+        let mut best: Option<((u8, i32), Move)> = None;
+        for mv in board.generate_legal_moves() {
+            let after = after(board, &mv);
+            let v = if dtz > 0 && after.is_checkmate() {
+                1
+            } else if is_capture(board, &mv) || is_pawn_move(board, &mv) {
+                WDL_TO_DTZ[(-self.wdl(&after)? + 2) as usize]
+            } else {
+                match -self.dtz(&after)? {
+                    v if v > 0 => v + 1,
+                    v if v < 0 => v - 1,
+                    _ => 0,
+                }
+            };
+            // Wins before the fifty move rule, cursed wins, draws, blessed losses and losses
+            let rank = match v {
+                v if v > 0 && v <= 100 => (4, -v),
+                v if v > 100 => (3, -v),
+                0 => (2, 0),
+                v if v < -100 => (1, -v),
+                v => (0, -v),
+            };
+            if best.is_none_or(|(best, _)| rank > best) {
+                best = Some((rank, mv));
+            }
+        }
+        best.map(|(_, mv)| mv)
+    }
+}
@@ -0,0 +1,924 @@
+use board::Board;
+use piece::{Color, PieceType};
+use square::Square;
+use std::convert::TryInto;
+use std::io;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+// Size of the index of the first pieces, by encoding type
+const PIVOT_FACTOR: [u64; 3] = [31332, 0, 462];
+
+#[rustfmt::skip]
+const TRIANGLE: [u8; 64] = [
+    6, 0, 1, 2, 2, 1, 0, 6,
+    0, 7, 3, 4, 4, 3, 7, 0,
+    1, 3, 8, 5, 5, 8, 3, 1,
+    2, 4, 5, 9, 9, 5, 4, 2,
+    2, 4, 5, 9, 9, 5, 4, 2,
+    1, 3, 8, 5, 5, 8, 3, 1,
+    0, 7, 3, 4, 4, 3, 7, 0,
+    6, 0, 1, 2, 2, 1, 0, 6,
+];
+
+// The square of each TRIANGLE value
+const INV_TRIANGLE: [u8; 10] = [1, 2, 3, 10, 11, 19, 0, 9, 18, 27];
+
+#[rustfmt::skip]
+const FLAP: [u8; 64] = [
+    0, 0, 0, 0, 0, 0, 0, 0,
+    0, 6, 12, 18, 18, 12, 6, 0,
+    1, 7, 13, 19, 19, 13, 7, 1,
+    2, 8, 14, 20, 20, 14, 8, 2,
+    3, 9, 15, 21, 21, 15, 9, 3,
+    4, 10, 16, 22, 22, 16, 10, 4,
+    5, 11, 17, 23, 23, 17, 11, 5,
+    0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+#[rustfmt::skip]
+const PTWIST: [u8; 64] = [
+    0, 0, 0, 0, 0, 0, 0, 0,
+    47, 35, 23, 11, 10, 22, 34, 46,
+    45, 33, 21, 9, 8, 20, 32, 44,
+    43, 31, 19, 7, 6, 18, 30, 42,
+    41, 29, 17, 5, 4, 16, 28, 40,
+    39, 27, 15, 3, 2, 14, 26, 38,
+    37, 25, 13, 1, 0, 12, 24, 36,
+    0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+#[rustfmt::skip]
+const INV_FLAP: [u8; 24] = [
+    8, 16, 24, 32, 40, 48,
+    9, 17, 25, 33, 41, 49,
+    10, 18, 26, 34, 42, 50,
+    11, 19, 27, 35, 43, 51,
+];
+
+const FILE_TO_FILE: [usize; 8] = [0, 1, 2, 3, 3, 2, 1, 0];
+
+// Where the map of DTZ values starts for each WDL value, and the flags telling that DTZ counts moves
+const WDL_TO_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+const PA_FLAGS: [u8; 5] = [8, 0, 0, 0, 4];
+
+// Positive above the a1-h8 diagonal, negative below it
+#[inline]
+fn off_diagonal(square: u8) -> i8 {
+    (square >> 3) as i8 - (square & 7) as i8
+}
+
+#[inline]
+fn flip_diagonal(square: u8) -> u8 {
+    ((square >> 3) | (square << 3)) & 63
+}
+
+// Squares below the diagonal are numbered 0 to 27 and squares on it 28 to 35, the rest is mirrored
+fn lower(square: u8) -> u64 {
+    let (file, rank) = ((square & 7) as u64, (square >> 3) as u64);
+    if file == rank {
+        return 28 + file;
+    }
+    let (file, rank) = if rank > file {
+        (rank, file)
+    } else {
+        (file, rank)
+    };
+    // Squares of the lower ranks come first, each rank starts right of the diagonal
+    (0..rank).map(|r| 7 - r).sum::<u64>() + file - rank - 1
+}
+
+// The a1-h8 diagonal is numbered 0 to 7 and the a8-h1 diagonal 8 to 15
+fn diagonal(square: u8) -> u64 {
+    let (file, rank) = ((square & 7) as u64, (square >> 3) as u64);
+    if file == rank {
+        file
+    } else if file + rank == 7 {
+        8 + rank
+    } else {
+        0
+    }
+}
+
+struct Indices {
+    // binomial[k][n] is n choose k + 1
+    binomial: [[u64; 64]; 5],
+    pawn_index: [[u64; 24]; 5],
+    pawn_factor: [[u64; 4]; 5],
+    // Index of both kings, the first one given by its TRIANGLE value
+    kk_index: [[i16; 64]; 10],
+}
+
+fn indices() -> &'static Indices {
+    static INDICES: OnceLock<Indices> = OnceLock::new();
+    INDICES.get_or_init(|| {
+        let mut binomial = [[0; 64]; 5];
+        for (k, row) in binomial.iter_mut().enumerate() {
+            for (n, value) in row.iter_mut().enumerate() {
+                let n = n as u64;
+                let mut f = n;
+                let mut l = 1;
+                for i in 1..=k as u64 {
+                    f *= n.saturating_sub(i);
+                    l *= i + 1;
+                }
+                *value = f / l;
+            }
+        }
+
+        let mut pawn_index = [[0; 24]; 5];
+        let mut pawn_factor = [[0; 4]; 5];
+        for i in 0..5 {
+            for (file, factor) in pawn_factor[i].iter_mut().enumerate() {
+                let mut s = 0;
+                for j in 6 * file..6 * file + 6 {
+                    pawn_index[i][j] = s;
+                    s += match i {
+                        0 => 1,
+                        _ => binomial[i - 1][PTWIST[INV_FLAP[j] as usize] as usize],
+                    };
+                }
+                *factor = s;
+            }
+        }
+
+        // Positions with both kings on the diagonal come last
+        let mut kk_index = [[-1; 64]; 10];
+        let mut next = 0;
+        for both_on_diagonal in [false, true] {
+            for (i, row) in kk_index.iter_mut().enumerate() {
+                let first = INV_TRIANGLE[i];
+                for second in 0..64u8 {
+                    let distance = ((first & 7) as i8 - (second & 7) as i8)
+                        .abs()
+                        .max(((first >> 3) as i8 - (second >> 3) as i8).abs());
+                    if distance <= 1
+                        || (off_diagonal(first) == 0 && off_diagonal(second) > 0)
+                        || (off_diagonal(first) == 0 && off_diagonal(second) == 0)
+                            != both_on_diagonal
+                    {
+                        continue;
+                    }
+                    row[second as usize] = next;
+                    next += 1;
+                }
+            }
+        }
+
+        Indices {
+            binomial,
+            pawn_index,
+            pawn_factor,
+            kk_index,
+        }
+    })
+}
+
+// The number of ways to place k identical pieces on n squares
+fn subfactor(k: u64, n: u64) -> u64 {
+    let mut f = n;
+    let mut l = 1;
+    for i in 1..k {
+        f *= n - i;
+        l *= i + 1;
+    }
+    f / l
+}
+
+// Reads the file, any part that is out of bounds means it is broken
+#[derive(Debug)]
+struct Bytes(Vec<u8>);
+
+impl Bytes {
+    #[inline]
+    fn u8(&self, at: usize) -> Option<u8> {
+        self.0.get(at).copied()
+    }
+
+    #[inline]
+    fn u16_le(&self, at: usize) -> Option<u16> {
+        Some(u16::from_le_bytes(self.0.get(at..at + 2)?.try_into().ok()?))
+    }
+
+    #[inline]
+    fn u32_le(&self, at: usize) -> Option<u32> {
+        Some(u32::from_le_bytes(self.0.get(at..at + 4)?.try_into().ok()?))
+    }
+
+    #[inline]
+    fn u32_be(&self, at: usize) -> Option<u32> {
+        Some(u32::from_be_bytes(self.0.get(at..at + 4)?.try_into().ok()?))
+    }
+
+    #[inline]
+    fn u64_be(&self, at: usize) -> Option<u64> {
+        Some(u64::from_be_bytes(self.0.get(at..at + 8)?.try_into().ok()?))
+    }
+}
+
+// The Huffman coded and pair compressed values of one side of a table
+#[derive(Debug, Default)]
+struct Pairs {
+    flags: u8,
+    // 0 for tables where every position has the value min_len
+    index_bits: u32,
+    block_size: u32,
+    min_len: u32,
+    // Where the parts start in the file
+    offset: usize,
+    sympat: usize,
+    index_table: usize,
+    size_table: usize,
+    data: usize,
+    // Sizes of the parts that come after the headers
+    index_size: usize,
+    size_size: usize,
+    data_size: usize,
+    symlen: Vec<u8>,
+    base: Vec<u64>,
+}
+
+// How the pieces of one side to move, or one file of the leading pawn, are indexed
+#[derive(Debug, Default)]
+struct Encoding {
+    // Codes of the pieces in the order they are indexed, 1 = pawn up to 6 = king, black + 8
+    pieces: Vec<u8>,
+    // The size of each group of pieces that is indexed together, at its first piece
+    norm: Vec<u8>,
+    factor: Vec<u64>,
+    size: u64,
+    pairs: Pairs,
+}
+
+pub(super) enum Dtz {
+    Found(i32),
+    // Only the other side to move is stored
+    OtherSide,
+}
+
+/*
+ * A .rtbw or .rtbz file. The name gives the material with the pieces of the
+ * first side as White, e.g. KRvKB, and the same table is used for positions
+ * with the colors swapped.
+ */
+#[derive(Debug)]
+pub(super) struct Table {
+    bytes: Bytes,
+    dtz: bool,
+    key: String,
+    symmetric: bool,
+    pieces: usize,
+    // Sides to move with values, WDL tables may leave out the second one
+    sides: usize,
+    // Pawns of the leading color first, the table is split by the file of its first pawn
+    pawns: [usize; 2],
+    enc_type: u8,
+    // One entry per file of the leading pawn, with one encoding per side to move
+    files: Vec<Vec<Encoding>>,
+    map: usize,
+    map_index: Vec<[usize; 4]>,
+    wide_map: Vec<bool>,
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "broken syzygy table")
+}
+
+impl Table {
+    pub(super) fn new(name: &str, data: Vec<u8>, dtz: bool) -> io::Result<Table> {
+        let (white, black) = name.split_once('v').ok_or_else(invalid)?;
+        let count = |side: &str, c: char| side.chars().filter(|&x| x == c).count();
+        let mut pawns = [count(white, 'P'), count(black, 'P')];
+        if pawns[1] > 0 && (pawns[0] == 0 || pawns[1] < pawns[0]) {
+            pawns.swap(0, 1);
+        }
+        let unique = "KQRBN"
+            .chars()
+            .map(|c| (count(white, c) == 1) as usize + (count(black, c) == 1) as usize)
+            .sum::<usize>();
+        let mut table = Table {
+            bytes: Bytes(data),
+            dtz,
+            key: name.to_string(),
+            symmetric: white == black,
+            pieces: white.len() + black.len(),
+            sides: 1,
+            pawns,
+            // Only these two are used in standard chess
+            enc_type: if unique >= 3 { 0 } else { 2 },
+            files: Vec::new(),
+            map: 0,
+            map_index: Vec::new(),
+            wide_map: Vec::new(),
+        };
+        table.init().ok_or_else(invalid)?;
+        Ok(table)
+    }
+
+    #[inline]
+    fn has_pawns(&self) -> bool {
+        self.pawns[0] > 0
+    }
+
+    // Reads the headers of the file, see the Syzygy probing code for the format
+    fn init(&mut self) -> Option<()> {
+        let magic = if self.dtz { DTZ_MAGIC } else { WDL_MAGIC };
+        if self.bytes.0.get(0..4)? != magic {
+            return None;
+        }
+        let flags = self.bytes.u8(4)?;
+        let split = !self.dtz && flags & 1 != 0;
+        let sides = if self.dtz { 1 } else { 1 + split as usize };
+        self.sides = sides;
+        let files = if self.has_pawns() { 4 } else { 1 };
+        let mut at = 5;
+
+        // The pieces and their order for every side, the high nibble is the second side
+        for file in 0..files {
+            let order_bytes = 1 + (self.has_pawns() && self.pawns[1] > 0) as usize;
+            let mut encodings = Vec::new();
+            for side in 0..if self.dtz { 1 } else { 2 } {
+                let nibble = |byte: u8| if side == 0 { byte & 0x0f } else { byte >> 4 };
+                let order = nibble(self.bytes.u8(at)?) as usize;
+                let order2 = match order_bytes {
+                    2 => nibble(self.bytes.u8(at + 1)?) as usize,
+                    _ => 0x0f,
+                };
+                let pieces = (0..self.pieces)
+                    .map(|i| self.bytes.u8(at + order_bytes + i).map(nibble))
+                    .collect::<Option<Vec<u8>>>()?;
+                encodings.push(self.encoding(pieces, order, order2, file)?);
+            }
+            self.files.push(encodings);
+            at += self.pieces + order_bytes;
+        }
+        at += at & 1;
+
+        for file in 0..files {
+            for side in 0..sides {
+                let size = self.files[file][side].size;
+                let (pairs, next) = self.pairs(at, size)?;
+                self.files[file][side].pairs = pairs;
+                at = next;
+            }
+        }
+
+        if self.dtz {
+            self.map = at;
+            for file in 0..files {
+                let flags = self.files[file][0].pairs.flags;
+                let wide = flags & 16 != 0;
+                let mut map_index = [0; 4];
+                if flags & 2 != 0 {
+                    if wide {
+                        at += at & 1;
+                    }
+                    for index in map_index.iter_mut() {
+                        if wide {
+                            *index = (at + 2 - self.map) / 2;
+                            at += 2 + 2 * self.bytes.u16_le(at)? as usize;
+                        } else {
+                            *index = at + 1 - self.map;
+                            at += 1 + self.bytes.u8(at)? as usize;
+                        }
+                    }
+                }
+                self.map_index.push(map_index);
+                self.wide_map.push(wide);
+            }
+            at += at & 1;
+        }
+
+        for file in 0..files {
+            for side in 0..sides {
+                let pairs = &mut self.files[file][side].pairs;
+                pairs.index_table = at;
+                at += pairs.index_size;
+            }
+        }
+        for file in 0..files {
+            for side in 0..sides {
+                let pairs = &mut self.files[file][side].pairs;
+                pairs.size_table = at;
+                at += pairs.size_size;
+            }
+        }
+        for file in 0..files {
+            for side in 0..sides {
+                let pairs = &mut self.files[file][side].pairs;
+                // Blocks start on 64 byte boundaries, empty ones don't matter
+                if pairs.data_size > 0 {
+                    at = (at + 0x3f) & !0x3f;
+                }
+                pairs.data = at;
+                at += pairs.data_size;
+            }
+        }
+        match at <= self.bytes.0.len() {
+            true => Some(()),
+            false => None,
+        }
+    }
+
+    fn encoding(
+        &self,
+        pieces: Vec<u8>,
+        order: usize,
+        order2: usize,
+        file: usize,
+    ) -> Option<Encoding> {
+        let indices = indices();
+        let mut norm = vec![0u8; self.pieces];
+        let mut factor = vec![0u64; self.pieces];
+
+        let mut i;
+        if self.has_pawns() {
+            norm[0] = self.pawns[0] as u8;
+            if self.pawns[1] > 0 {
+                norm[self.pawns[0]] = self.pawns[1] as u8;
+            }
+            i = self.pawns[0] + self.pawns[1];
+        } else {
+            norm[0] = if self.enc_type == 0 { 3 } else { 2 };
+            i = norm[0] as usize;
+        }
+        while i < self.pieces {
+            let group = pieces[i..]
+                .iter()
+                .take_while(|&&piece| piece == pieces[i])
+                .count();
+            norm[i] = group as u8;
+            i += group;
+        }
+
+        let mut size = 1u64;
+        let mut i = norm[0] as usize;
+        if self.has_pawns() && order2 < 0x0f {
+            i += norm[i] as usize;
+        }
+        let mut free = 64 - i as u64;
+        let mut k = 0;
+        while i < self.pieces || k == order || k == order2 {
+            if k == order {
+                factor[0] = size;
+                size *= match self.has_pawns() {
+                    true => indices.pawn_factor[norm[0] as usize - 1][file],
+                    false => PIVOT_FACTOR[self.enc_type as usize],
+                };
+            } else if k == order2 {
+                factor[norm[0] as usize] = size;
+                size *= subfactor(norm[norm[0] as usize] as u64, 48 - norm[0] as u64);
+            } else {
+                factor[i] = size;
+                size *= subfactor(norm[i] as u64, free);
+                free -= norm[i] as u64;
+                i += norm[i] as usize;
+            }
+            k += 1;
+            if k > self.pieces + 1 {
+                return None;
+            }
+        }
+
+        Some(Encoding {
+            pieces,
+            norm,
+            factor,
+            size,
+            pairs: Pairs::default(),
+        })
+    }
+
+    // The header of compressed values starting at at, and where the next one starts
+    fn pairs(&self, at: usize, size: u64) -> Option<(Pairs, usize)> {
+        let bytes = &self.bytes;
+        let flags = bytes.u8(at)?;
+        if flags & 0x80 != 0 {
+            let pairs = Pairs {
+                flags,
+                min_len: if self.dtz {
+                    0
+                } else {
+                    bytes.u8(at + 1)? as u32
+                },
+                ..Default::default()
+            };
+            return Some((pairs, at + 2));
+        }
+
+        let block_size = bytes.u8(at + 1)? as u32;
+        let index_bits = bytes.u8(at + 2)? as u32;
+        let real_blocks = bytes.u32_le(at + 4)? as usize;
+        let blocks = real_blocks + bytes.u8(at + 3)? as usize;
+        let max_len = bytes.u8(at + 8)? as u32;
+        let min_len = bytes.u8(at + 9)? as u32;
+        if min_len == 0 || max_len < min_len || max_len > 64 || index_bits == 0 || index_bits > 63 {
+            return None;
+        }
+        let h = (max_len - min_len + 1) as usize;
+        let symbols = bytes.u16_le(at + 10 + 2 * h)? as usize;
+        let sympat = at + 12 + 2 * h;
+        let next = sympat + 3 * symbols + (symbols & 1);
+
+        let mut pairs = Pairs {
+            flags,
+            index_bits,
+            block_size,
+            min_len,
+            offset: at + 10,
+            sympat,
+            index_size: 6 * ((size + (1 << index_bits) - 1) >> index_bits) as usize,
+            size_size: 2 * blocks,
+            data_size: (1usize << block_size) * real_blocks,
+            symlen: vec![0; symbols],
+            ..Default::default()
+        };
+
+        let mut done = vec![false; symbols];
+        for symbol in 0..symbols {
+            self.symlen(&mut pairs, symbol, &mut done)?;
+        }
+
+        let mut base = vec![0u64; h];
+        for i in (0..h - 1).rev() {
+            base[i] = base[i + 1]
+                .wrapping_add(self.offset(&pairs, i)? as u64)
+                .wrapping_sub(self.offset(&pairs, i + 1)? as u64)
+                / 2;
+        }
+        for (i, value) in base.iter_mut().enumerate() {
+            *value = value.checked_shl(64 - (min_len + i as u32)).unwrap_or(0);
+        }
+        pairs.base = base;
+        Some((pairs, next))
+    }
+
+    #[inline]
+    fn offset(&self, pairs: &Pairs, i: usize) -> Option<u16> {
+        self.bytes.u16_le(pairs.offset + 2 * i)
+    }
+
+    // The two symbols a symbol stands for, or None for one that is a single value
+    fn children(&self, pairs: &Pairs, symbol: usize) -> Option<Option<(usize, usize)>> {
+        let w = pairs.sympat + 3 * symbol;
+        let (w0, w1, w2) = (
+            self.bytes.u8(w)? as usize,
+            self.bytes.u8(w + 1)? as usize,
+            self.bytes.u8(w + 2)? as usize,
+        );
+        let right = (w2 << 4) | (w1 >> 4);
+        if right == 0x0fff {
+            return Some(None);
+        }
+        let left = ((w1 & 0x0f) << 8) | w0;
+        match left < pairs.symlen.len() && right < pairs.symlen.len() {
+            true => Some(Some((left, right))),
+            false => None,
+        }
+    }
+
+    // How many values a symbol stands for, minus one
+    fn symlen(&self, pairs: &mut Pairs, symbol: usize, done: &mut [bool]) -> Option<()> {
+        if done[symbol] {
+            return Some(());
+        }
+        done[symbol] = true;
+        if let Some((left, right)) = self.children(pairs, symbol)? {
+            self.symlen(pairs, left, done)?;
+            self.symlen(pairs, right, done)?;
+            pairs.symlen[symbol] = pairs.symlen[left]
+                .wrapping_add(pairs.symlen[right])
+                .wrapping_add(1);
+        }
+        Some(())
+    }
+
+    // The value stored for the position with the index
+    fn decompress(&self, pairs: &Pairs, index: u64) -> Option<u8> {
+        if pairs.index_bits == 0 {
+            return Some(pairs.min_len as u8);
+        }
+        let bytes = &self.bytes;
+        let main = (index >> pairs.index_bits) as usize;
+        let mut literal =
+            (index & ((1 << pairs.index_bits) - 1)) as i64 - (1i64 << (pairs.index_bits - 1));
+        let mut block = bytes.u32_le(pairs.index_table + 6 * main)? as usize;
+        literal += bytes.u16_le(pairs.index_table + 6 * main + 4)? as i64;
+        let block_length = |block: usize| bytes.u16_le(pairs.size_table + 2 * block);
+        while literal < 0 {
+            block = block.checked_sub(1)?;
+            literal += block_length(block)? as i64 + 1;
+        }
+        while literal > block_length(block)? as i64 {
+            literal -= block_length(block)? as i64 + 1;
+            block += 1;
+        }
+
+        let mut at = pairs.data + (block << pairs.block_size);
+        let mut code = bytes.u64_be(at)?;
+        at += 8;
+        let mut bits = 0;
+        let min_len = pairs.min_len as usize;
+        let mut symbol;
+        loop {
+            let mut l = min_len;
+            while code < *pairs.base.get(l - min_len)? {
+                l += 1;
+            }
+            symbol = self.offset(pairs, l - min_len)? as usize
+                + ((code - pairs.base[l - min_len]) >> (64 - l)) as usize;
+            let length = *pairs.symlen.get(symbol)? as i64 + 1;
+            if literal < length {
+                break;
+            }
+            literal -= length;
+            code <<= l;
+            bits += l;
+            if bits >= 32 {
+                bits -= 32;
+                code |= (bytes.u32_be(at)? as u64) << bits;
+                at += 4;
+            }
+        }
+
+        while let Some((left, right)) = self.children(pairs, symbol)? {
+            let length = pairs.symlen[left] as i64 + 1;
+            if literal < length {
+                symbol = left;
+            } else {
+                literal -= length;
+                symbol = right;
+            }
+        }
+        bytes.u8(pairs.sympat + 3 * symbol)
+    }
+
+    fn encode_piece(&self, encoding: &Encoding, squares: &mut [u8]) -> Option<u64> {
+        let indices = indices();
+        let n = squares.len();
+        if squares[0] & 0x04 != 0 {
+            squares.iter_mut().for_each(|square| *square ^= 0x07);
+        }
+        if squares[0] & 0x20 != 0 {
+            squares.iter_mut().for_each(|square| *square ^= 0x38);
+        }
+        let limit = if self.enc_type == 0 { 3 } else { 2 };
+        if let Some(&first) = squares[..limit]
+            .iter()
+            .find(|&&square| off_diagonal(square) != 0)
+        {
+            if off_diagonal(first) > 0 {
+                squares
+                    .iter_mut()
+                    .for_each(|square| *square = flip_diagonal(*square));
+            }
+        }
+
+        let mut index;
+        let mut i;
+        if self.enc_type == 0 {
+            let s = |k: usize| squares[k] as u64;
+            let a = (s(1) > s(0)) as u64;
+            let b = (s(2) > s(0)) as u64 + (s(2) > s(1)) as u64;
+            index = if off_diagonal(squares[0]) != 0 {
+                TRIANGLE[squares[0] as usize] as u64 * 63 * 62 + (s(1) - a) * 62 + s(2) - b
+            } else if off_diagonal(squares[1]) != 0 {
+                6 * 63 * 62 + diagonal(squares[0]) * 28 * 62 + lower(squares[1]) * 62 + s(2) - b
+            } else if off_diagonal(squares[2]) != 0 {
+                6 * 63 * 62
+                    + 4 * 28 * 62
+                    + diagonal(squares[0]) * 7 * 28
+                    + (diagonal(squares[1]) - a) * 28
+                    + lower(squares[2])
+            } else {
+                6 * 63 * 62
+                    + 4 * 28 * 62
+                    + 4 * 7 * 28
+                    + diagonal(squares[0]) * 7 * 6
+                    + (diagonal(squares[1]) - a) * 6
+                    + diagonal(squares[2])
+                    - b
+            };
+            i = 3;
+        } else {
+            let kk = indices.kk_index[TRIANGLE[squares[0] as usize] as usize][squares[1] as usize];
+            if kk < 0 {
+                return None;
+            }
+            index = kk as u64;
+            i = 2;
+        }
+        index *= encoding.factor[0];
+
+        while i < n {
+            let group = encoding.norm[i] as usize;
+            index += group_index(&indices.binomial, squares, i, group, 0) * encoding.factor[i];
+            i += group;
+        }
+        Some(index)
+    }
+
+    fn encode_pawn(&self, encoding: &Encoding, squares: &mut [u8]) -> u64 {
+        let indices = indices();
+        let n = squares.len();
+        if squares[0] & 0x04 != 0 {
+            squares.iter_mut().for_each(|square| *square ^= 0x07);
+        }
+        let leading = encoding.norm[0] as usize;
+        squares[1..leading]
+            .sort_unstable_by_key(|&square| std::cmp::Reverse(PTWIST[square as usize]));
+
+        let t = leading - 1;
+        let mut index = indices.pawn_index[t][FLAP[squares[0] as usize] as usize];
+        for i in (1..=t).rev() {
+            index += indices.binomial[t - i][PTWIST[squares[i] as usize] as usize];
+        }
+        index *= encoding.factor[0];
+
+        // The pawns of the other color can't stand on the first and last rank either
+        let mut i = leading;
+        if self.pawns[1] > 0 {
+            let group = encoding.norm[i] as usize;
+            index += group_index(&indices.binomial, squares, i, group, 8) * encoding.factor[i];
+            i += group;
+        }
+        while i < n {
+            let group = encoding.norm[i] as usize;
+            index += group_index(&indices.binomial, squares, i, group, 0) * encoding.factor[i];
+            i += group;
+        }
+        index
+    }
+
+    // Adds the squares of pieces[range] in the order of the table, None if the material doesn't match
+    fn squares(
+        &self,
+        board: &Board,
+        pieces: &[u8],
+        range: Range<usize>,
+        cmirror: u8,
+        mirror: u8,
+        squares: &mut Vec<u8>,
+    ) -> Option<()> {
+        let mut i = range.start;
+        while i < range.end {
+            let code = pieces[i] ^ cmirror;
+            let color = if code & 8 == 0 {
+                Color::White
+            } else {
+                Color::Black
+            };
+            let piece_type = piece_type(code & 7)?;
+            let found = squares.len();
+            for &square in Square::ALL.iter() {
+                if board
+                    .piece_at(square)
+                    .is_some_and(|piece| piece.color == color && piece.piece_type == piece_type)
+                {
+                    squares.push(square.index() as u8 ^ mirror);
+                }
+            }
+            if squares.len() == found {
+                return None;
+            }
+            i += squares.len() - found;
+        }
+        match i == range.end && squares.len() == range.end {
+            true => Some(()),
+            false => None,
+        }
+    }
+
+    // Which side of the table to use and how to mirror the board to match it
+    fn orientation(&self, board: &Board, key: &str) -> (usize, u8, u8) {
+        let white = board.whose_turn() == Color::White;
+        if self.symmetric {
+            match white {
+                true => (0, 0, 0),
+                false => (0, 8, 0x38),
+            }
+        } else if key != self.key {
+            (white as usize, 8, 0x38)
+        } else {
+            (!white as usize, 0, 0)
+        }
+    }
+
+    // The file of the leading pawn and the index of the position in the encoding of side
+    fn index(&self, board: &Board, key: &str, side: usize) -> Option<(usize, u64)> {
+        let (_, cmirror, mirror) = self.orientation(board, key);
+        let mut squares = Vec::with_capacity(self.pieces);
+        if !self.has_pawns() {
+            let encoding = self.files[0].get(side)?;
+            self.squares(
+                board,
+                &encoding.pieces,
+                0..self.pieces,
+                cmirror,
+                0,
+                &mut squares,
+            )?;
+            return Some((0, self.encode_piece(encoding, &mut squares)?));
+        }
+
+        let leading = &self.files[0][0].pieces;
+        self.squares(
+            board,
+            leading,
+            0..self.pawns[0],
+            cmirror,
+            mirror,
+            &mut squares,
+        )?;
+        for i in 1..self.pawns[0] {
+            if FLAP[squares[0] as usize] > FLAP[squares[i] as usize] {
+                squares.swap(0, i);
+            }
+        }
+        let file = FILE_TO_FILE[(squares[0] & 7) as usize];
+        let encoding = self.files[file].get(side)?;
+        let found = squares.len();
+        self.squares(
+            board,
+            &encoding.pieces,
+            found..self.pieces,
+            cmirror,
+            mirror,
+            &mut squares,
+        )?;
+        Some((file, self.encode_pawn(encoding, &mut squares)))
+    }
+
+    // Win (2), cursed win (1), draw (0), blessed loss (-1) or loss (-2), ignoring captures
+    pub(super) fn probe_wdl(&self, board: &Board, key: &str) -> Option<i32> {
+        let (side, _, _) = self.orientation(board, key);
+        if side >= self.sides {
+            return None;
+        }
+        let (file, index) = self.index(board, key, side)?;
+        Some(self.decompress(&self.files[file][side].pairs, index)? as i32 - 2)
+    }
+
+    // Distance to zeroing the fifty move counter, not counting the first move
+    pub(super) fn probe_dtz(&self, board: &Board, key: &str, wdl: i32) -> Option<Dtz> {
+        let (side, _, _) = self.orientation(board, key);
+        // Only one side to move is stored, with the pieces as seen by it
+        let (file, index) = self.index(board, key, 0)?;
+        let encoding = &self.files[file][0];
+        let flags = encoding.pairs.flags;
+        if (flags & 1) as usize != side && !self.symmetric {
+            return Some(Dtz::OtherSide);
+        }
+
+        let mut value = self.decompress(&encoding.pairs, index)? as usize;
+        if flags & 2 != 0 {
+            let at = self.map_index[file][WDL_TO_MAP[(wdl + 2) as usize]] + value;
+            value = match self.wide_map[file] {
+                true => self.bytes.u16_le(self.map + 2 * at)? as usize,
+                false => self.bytes.u8(self.map + at)? as usize,
+            };
+        }
+        let mut value = value as i32;
+        if flags & PA_FLAGS[(wdl + 2) as usize] == 0 || wdl & 1 != 0 {
+            value *= 2;
+        }
+        Some(Dtz::Found(value))
+    }
+}
+
+// The index of a group of identical pieces among the squares the earlier pieces left free
+fn group_index(
+    binomial: &[[u64; 64]; 5],
+    squares: &mut [u8],
+    start: usize,
+    group: usize,
+    skip: u8,
+) -> u64 {
+    squares[start..start + group].sort_unstable();
+    let mut index = 0;
+    for m in start..start + group {
+        let square = squares[m];
+        let below = squares[..start]
+            .iter()
+            .filter(|&&other| square > other)
+            .count() as u8;
+        index += binomial[m - start][(square - below - skip) as usize];
+    }
+    index
+}
+
+#[inline]
+fn piece_type(code: u8) -> Option<PieceType> {
+    match code {
+        1 => Some(PieceType::Pawn),
+        2 => Some(PieceType::Knight),
+        3 => Some(PieceType::Bishop),
+        4 => Some(PieceType::Rook),
+        5 => Some(PieceType::Queen),
+        6 => Some(PieceType::King),
+        _ => None,
+    }
+}
@@ -0,0 +1,527 @@
+use board::{Board, BoardError, Move};
+use piece::{Color, Piece, PieceType};
+use square::Square;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+mod generate;
+
+// Every piece makes a table 64 times bigger, four of them already take 32 MB
+pub const MAX_PIECES: usize = 4;
+
+// Start of table files, followed by the material signature and one byte per position
+const MAGIC: &[u8; 8] = b"CHAPITB1";
+
+/*
+ * How positions are stored, from the point of view of the player to move:
+ * DRAW, 1 to MAX_PLY for a win with mate after that many half moves, LOSS + n
+ * when mated after n half moves and INVALID for positions that can't occur.
+ */
+const DRAW: u8 = 0;
+const MAX_PLY: u8 = 125;
+const LOSS: u8 = 128;
+const INVALID: u8 = 255;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Wdl {
+    Win,
+    Draw,
+    Loss,
+}
+
+// The outcome of a position with perfect play, for the player to move
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Probe {
+    pub wdl: Wdl,
+    // Half moves until mate, 0 for draws and positions that are already checkmate
+    pub dtm: u32,
+}
+
+impl Probe {
+    fn from_value(value: u8) -> Option<Probe> {
+        match value {
+            DRAW => Some(Probe {
+                wdl: Wdl::Draw,
+                dtm: 0,
+            }),
+            INVALID => None,
+            value if value >= LOSS => Some(Probe {
+                wdl: Wdl::Loss,
+                dtm: (value - LOSS) as u32,
+            }),
+            value => Some(Probe {
+                wdl: Wdl::Win,
+                dtm: value as u32,
+            }),
+        }
+    }
+}
+
+// The value of a position for the player who moved into it, given its value for the opponent
+#[inline]
+fn back(value: u8) -> u8 {
+    match value {
+        DRAW => DRAW,
+        value if value >= LOSS => value - LOSS + 1,
+        value => {
+            assert!(value < MAX_PLY, "mate too long for a tablebase");
+            LOSS + value + 1
+        }
+    }
+}
+
+// Higher is better for the player to move: quick wins, then draws, then slow losses
+#[inline]
+fn score(value: u8) -> i32 {
+    match value {
+        DRAW => 0,
+        value if value >= LOSS => -1000 + (value - LOSS) as i32,
+        value => 1000 - value as i32,
+    }
+}
+
+// The order of pieces in a signature, strongest first
+#[inline]
+fn order(piece_type: PieceType) -> u8 {
+    match piece_type {
+        PieceType::King => 0,
+        PieceType::Queen => 1,
+        PieceType::Rook => 2,
+        PieceType::Bishop => 3,
+        PieceType::Knight => 4,
+        PieceType::Pawn => 5,
+    }
+}
+
+/*
+ * The pieces of both players, written like "KQK" or "KRvKB" with the white
+ * pieces first. Each side starts with its king.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Material {
+    white: Vec<PieceType>,
+    black: Vec<PieceType>,
+}
+
+impl Material {
+    fn new(mut white: Vec<PieceType>, mut black: Vec<PieceType>) -> Self {
+        white.sort_by_key(|&piece_type| order(piece_type));
+        black.sort_by_key(|&piece_type| order(piece_type));
+        Material { white, black }
+    }
+
+    // The material on the board
+    pub fn of(board: &Board) -> Self {
+        let mut white = Vec::new();
+        let mut black = Vec::new();
+        for &square in Square::ALL.iter() {
+            match board.piece_at(square) {
+                Some(piece) if piece.color == Color::White => white.push(piece.piece_type),
+                Some(piece) => black.push(piece.piece_type),
+                None => {}
+            }
+        }
+        Material::new(white, black)
+    }
+
+    // Kings first
+    #[inline]
+    pub fn white(&self) -> &[PieceType] {
+        &self.white
+    }
+
+    #[inline]
+    pub fn black(&self) -> &[PieceType] {
+        &self.black
+    }
+
+    // The same material with the colors swapped
+    pub fn flip(&self) -> Self {
+        Material {
+            white: self.black.clone(),
+            black: self.white.clone(),
+        }
+    }
+
+    // Every piece, white ones first, in the order they are indexed in a table
+    fn pieces(&self) -> Vec<Piece> {
+        let white = self.white.iter().map(|&piece_type| Piece {
+            piece_type,
+            color: Color::White,
+        });
+        let black = self.black.iter().map(|&piece_type| Piece {
+            piece_type,
+            color: Color::Black,
+        });
+        white.chain(black).collect()
+    }
+
+    // Tables are generated with the stronger side as White and looked up both ways
+    fn canonical(&self) -> Self {
+        let strength = |side: &[PieceType]| {
+            (
+                std::cmp::Reverse(side.len()),
+                side.iter()
+                    .map(|&piece_type| order(piece_type))
+                    .collect::<Vec<_>>(),
+            )
+        };
+        match strength(&self.white) <= strength(&self.black) {
+            true => self.clone(),
+            false => self.flip(),
+        }
+    }
+
+    // Like Board::is_insufficient_material(), no table is needed for it
+    fn is_insufficient(&self) -> bool {
+        let pieces = self.white.iter().chain(self.black.iter());
+        let mut minor_pieces = 0;
+        for &piece_type in pieces {
+            match piece_type {
+                PieceType::Pawn | PieceType::Rook | PieceType::Queen => return false,
+                PieceType::Knight | PieceType::Bishop => minor_pieces += 1,
+                PieceType::King => {}
+            }
+        }
+        minor_pieces <= 1
+    }
+
+    // The material left after a capture or a promotion
+    fn successors(&self) -> Vec<Material> {
+        let mut successors = Vec::new();
+        for white in [true, false] {
+            let side = if white { &self.white } else { &self.black };
+            for (i, &piece_type) in side.iter().enumerate().skip(1) {
+                let mut replacements = vec![None];
+                if piece_type == PieceType::Pawn {
+                    replacements.extend(
+                        [
+                            PieceType::Queen,
+                            PieceType::Rook,
+                            PieceType::Bishop,
+                            PieceType::Knight,
+                        ]
+                        .iter()
+                        .map(|&promotion| Some(promotion)),
+                    );
+                }
+                for replacement in replacements {
+                    let mut changed = side.clone();
+                    match replacement {
+                        Some(promotion) => changed[i] = promotion,
+                        None => {
+                            changed.remove(i);
+                        }
+                    }
+                    successors.push(match white {
+                        true => Material::new(changed, self.black.clone()),
+                        false => Material::new(self.white.clone(), changed),
+                    });
+                }
+            }
+        }
+        successors
+    }
+}
+
+impl fmt::Display for Material {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &piece_type in self.white.iter().chain(self.black.iter()) {
+            write!(f, "{}", piece_type.to_char())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Material {
+    type Err = BoardError;
+
+    // Both "KRK" and "KRvK" work
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (white, black) = match s.find('v') {
+            Some(i) => (&s[..i], &s[i + 1..]),
+            None => match s.get(1..).and_then(|rest| rest.find('K')) {
+                Some(i) => s.split_at(i + 1),
+                None => return Err(BoardError::InvalidMaterial),
+            },
+        };
+        let side = |pieces: &str| -> Result<Vec<PieceType>, BoardError> {
+            if !pieces.starts_with('K') {
+                return Err(BoardError::InvalidMaterial);
+            }
+            let pieces = pieces
+                .chars()
+                .map(|c| match c.is_ascii_uppercase() {
+                    true => PieceType::from_char(c),
+                    false => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or(BoardError::InvalidMaterial)?;
+            match pieces
+                .iter()
+                .filter(|&&piece_type| piece_type == PieceType::King)
+                .count()
+            {
+                1 => Ok(pieces),
+                _ => Err(BoardError::InvalidMaterial),
+            }
+        };
+        let material = Material::new(side(white)?, side(black)?);
+        if material.white.len() + material.black.len() > MAX_PIECES {
+            return Err(BoardError::InvalidMaterial);
+        }
+        Ok(material)
+    }
+}
+
+// The index of a position, made of the side to move and the square of every piece
+#[inline]
+fn encode(squares: &[u8], turn: Color) -> usize {
+    let turn = match turn {
+        Color::White => 0,
+        Color::Black => 1,
+    };
+    squares
+        .iter()
+        .fold(turn, |index, &square| (index << 6) | square as usize)
+}
+
+#[inline]
+fn decode(index: usize, count: usize) -> ([u8; MAX_PIECES], Color) {
+    let mut squares = [0; MAX_PIECES];
+    for (i, square) in squares[..count].iter_mut().enumerate() {
+        *square = ((index >> (6 * (count - 1 - i))) & 63) as u8;
+    }
+    let turn = match index >> (6 * count) {
+        0 => Color::White,
+        _ => Color::Black,
+    };
+    (squares, turn)
+}
+
+// Pieces that are the same are sorted by square, so every position has a single index
+fn canonicalize(pieces: &[Piece], squares: &mut [u8]) {
+    let mut start = 0;
+    while start < pieces.len() {
+        let end = start
+            + pieces[start..]
+                .iter()
+                .take_while(|&&piece| piece == pieces[start])
+                .count();
+        squares[start..end].sort_unstable();
+        start = end;
+    }
+}
+
+/*
+ * Win, draw or loss and the distance to mate of every position with some
+ * material. Castling, en passant and the fifty move rule are not taken into account.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    material: Material,
+    values: Vec<u8>,
+}
+
+impl Table {
+    #[inline]
+    pub fn material(&self) -> &Material {
+        &self.material
+    }
+
+    // The number of entries, including positions that can't occur
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    // Half moves of the longest forced mate, 0 if no position is won
+    pub fn longest_mate(&self) -> u32 {
+        self.values
+            .iter()
+            .filter(|&&value| value != DRAW && value < LOSS)
+            .max()
+            .map_or(0, |&value| value as u32)
+    }
+
+    // None if the board has other material, with flipped the colors of the board are swapped
+    fn value(&self, board: &Board, flipped: bool) -> Option<u8> {
+        let pieces = self.material.pieces();
+        let mut squares: [Option<u8>; MAX_PIECES] = [None; MAX_PIECES];
+        for &square in Square::ALL.iter() {
+            let mut piece = match board.piece_at(square) {
+                Some(piece) => piece,
+                None => continue,
+            };
+            let mut square = square.index() as u8;
+            if flipped {
+                piece.color = piece.color.flip();
+                square ^= 56;
+            }
+            let slot = (0..pieces.len()).find(|&i| pieces[i] == piece && squares[i].is_none())?;
+            squares[slot] = Some(square);
+        }
+
+        let mut found = [0; MAX_PIECES];
+        for (i, square) in squares[..pieces.len()].iter().enumerate() {
+            found[i] = (*square)?;
+        }
+        canonicalize(&pieces, &mut found[..pieces.len()]);
+        let turn = match flipped {
+            true => board.whose_turn().flip(),
+            false => board.whose_turn(),
+        };
+        match self.values[encode(&found[..pieces.len()], turn)] {
+            INVALID => None,
+            value => Some(value),
+        }
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let material = self.material.to_string();
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[material.len() as u8])?;
+        writer.write_all(material.as_bytes())?;
+        writer.write_all(&self.values)
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Table> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "not a tablebase");
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        if !bytes.starts_with(MAGIC) || bytes.len() <= MAGIC.len() {
+            return Err(invalid());
+        }
+        let start = MAGIC.len() + 1 + bytes[MAGIC.len()] as usize;
+        let material = bytes
+            .get(MAGIC.len() + 1..start)
+            .and_then(|material| std::str::from_utf8(material).ok())
+            .and_then(|material| material.parse::<Material>().ok())
+            .ok_or_else(invalid)?;
+        let values = bytes[start.min(bytes.len())..].to_vec();
+        if values.len() != 2 << (6 * material.pieces().len()) {
+            return Err(invalid());
+        }
+        Ok(Table { material, values })
+    }
+}
+
+/*
+ * A set of tables that can be probed for any position with their material,
+ * whichever color has it. Generating a table also generates the ones that
+ * captures and promotions lead to.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Tablebase {
+    tables: HashMap<Material, Table>,
+}
+
+impl Tablebase {
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // E.g. "KQK", "KRK", "KPK" or "KBNK", the last one takes a while
+    pub fn generate(&mut self, material: &str) -> Result<(), BoardError> {
+        let material = material.parse::<Material>()?;
+        self.generate_material(material.canonical());
+        Ok(())
+    }
+
+    fn generate_material(&mut self, material: Material) {
+        if material.is_insufficient()
+            || self.tables.contains_key(&material)
+            || self.tables.contains_key(&material.flip())
+        {
+            return;
+        }
+        for successor in material.successors() {
+            self.generate_material(successor.canonical());
+        }
+        let table = generate::generate(&material, self);
+        self.add(table);
+    }
+
+    // Replaces the table for the same material, if there is one
+    pub fn add(&mut self, table: Table) {
+        self.tables.insert(table.material.clone(), table);
+    }
+
+    pub fn table(&self, material: &Material) -> Option<&Table> {
+        self.tables.get(material)
+    }
+
+    pub fn tables(&self) -> impl Iterator<Item = &Table> {
+        self.tables.values()
+    }
+
+    // Writes every table to a file like KQK.tb in dir
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> io::Result<()> {
+        for table in self.tables.values() {
+            let path = dir.as_ref().join(format!("{}.tb", table.material));
+            table.write(io::BufWriter::new(fs::File::create(path)?))?;
+        }
+        Ok(())
+    }
+
+    // Reads every .tb file in dir
+    pub fn load<P: AsRef<Path>>(dir: P) -> io::Result<Tablebase> {
+        let mut tablebase = Tablebase::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "tb") {
+                tablebase.add(Table::read(fs::File::open(path)?)?);
+            }
+        }
+        Ok(tablebase)
+    }
+
+    fn value(&self, board: &Board) -> Option<u8> {
+        if board.is_insufficient_material() {
+            return Some(DRAW);
+        }
+        if board.castling_rights(Color::White) != (false, false)
+            || board.castling_rights(Color::Black) != (false, false)
+        {
+            return None;
+        }
+        let material = Material::of(board);
+        match self.tables.get(&material) {
+            Some(table) => table.value(board, false),
+            None => self.tables.get(&material.flip())?.value(board, true),
+        }
+    }
+
+    // None if there is no table for the material on the board or castling is still possible
+    pub fn probe(&self, board: &Board) -> Option<Probe> {
+        Probe::from_value(self.value(board)?)
+    }
+
+    /*
+     * The move that mates the fastest when winning, keeps the draw when drawing
+     * and holds out the longest when losing. None if the position can't be probed
+     * or the game is over.
+     */
+    pub fn best_move(&self, board: &Board) -> Option<Move> {
+        let mut best: Option<(i32, Move)> = None;
+        for mv in board.generate_legal_moves() {
+            let mut after = *board;
+            after.make_move(&mv).unwrap();
+            let score = score(back(self.value(&after)?));
+            if best.is_none_or(|(best, _)| score > best) {
+                best = Some((score, mv));
+            }
+        }
+        best.map(|(_, mv)| mv)
+    }
+}
@@ -0,0 +1,241 @@
+use board::{Board, BoardBuilder, BoardError};
+use piece::{Color, Piece, PieceType};
+use square::Square;
+use tablebase::*;
+
+const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_STEPS: [(i8, i8); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+const ROOK_STEPS: [(i8, i8); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const BISHOP_STEPS: [(i8, i8); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+#[inline]
+fn step(square: u8, (df, dr): (i8, i8)) -> Option<u8> {
+    Square::from_index(square as usize)
+        .and_then(|square| square.offset(df, dr))
+        .map(|square| square.index() as u8)
+}
+
+// False for positions with two pieces on a square and for the other orders of identical pieces
+fn is_canonical(pieces: &[Piece], squares: &[u8]) -> bool {
+    for i in 0..squares.len() {
+        for j in i + 1..squares.len() {
+            if squares[i] == squares[j] || (pieces[i] == pieces[j] && squares[i] > squares[j]) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn build(pieces: &[Piece], squares: &[u8], turn: Color) -> Result<Board, BoardError> {
+    let mut builder = BoardBuilder::new().turn(turn);
+    for (&piece, &square) in pieces.iter().zip(squares.iter()) {
+        builder = builder.piece(Square::from_index(square as usize).unwrap(), piece);
+    }
+    builder.build()
+}
+
+// The squares piece could have come from to reach square without capturing or promoting
+fn origins(piece: Piece, square: u8, occupied: u64, origins: &mut Vec<u8>) {
+    let empty = |square: u8| occupied & (1 << square) == 0;
+    let mut leap = |steps: &[(i8, i8)]| {
+        for &offset in steps {
+            origins.extend(step(square, offset).filter(|&from| empty(from)));
+        }
+    };
+    match piece.piece_type {
+        PieceType::Knight => leap(&KNIGHT_STEPS),
+        PieceType::King => leap(&KING_STEPS),
+        PieceType::Pawn => {
+            let (back, second_rank) = match piece.color {
+                Color::White => (-1, 1),
+                Color::Black => (1, 6),
+            };
+            let rank = |square: u8| (square / 8) as i8;
+            if let Some(from) = step(square, (0, back)).filter(|&from| empty(from)) {
+                // Pawns never stand on the first or last rank
+                if (1..=6).contains(&rank(from)) {
+                    origins.push(from);
+                }
+                if rank(from) + back == second_rank {
+                    origins.extend(step(from, (0, back)).filter(|&from| empty(from)));
+                }
+            }
+        }
+        PieceType::Rook | PieceType::Bishop | PieceType::Queen => {
+            let rays: &[(i8, i8)] = match piece.piece_type {
+                PieceType::Rook => &ROOK_STEPS,
+                PieceType::Bishop => &BISHOP_STEPS,
+                _ => &KING_STEPS,
+            };
+            for &offset in rays {
+                let mut from = square;
+                while let Some(next) = step(from, offset).filter(|&next| empty(next)) {
+                    origins.push(next);
+                    from = next;
+                }
+            }
+        }
+    }
+}
+
+// The indexes of the positions the player who just moved could have come from
+fn predecessors(pieces: &[Piece], index: usize, before: &mut Vec<usize>) {
+    before.clear();
+    let (squares, turn) = decode(index, pieces.len());
+    let squares = &squares[..pieces.len()];
+    let mover = turn.flip();
+    let occupied = squares
+        .iter()
+        .fold(0u64, |occupied, &square| occupied | (1 << square));
+
+    let mut from = Vec::new();
+    for (slot, &piece) in pieces.iter().enumerate() {
+        if piece.color != mover {
+            continue;
+        }
+        from.clear();
+        origins(piece, squares[slot], occupied, &mut from);
+        for &origin in from.iter() {
+            let mut moved = [0; MAX_PIECES];
+            moved[..pieces.len()].copy_from_slice(squares);
+            moved[slot] = origin;
+            canonicalize(pieces, &mut moved[..pieces.len()]);
+            before.push(encode(&moved[..pieces.len()], mover));
+        }
+    }
+}
+
+/*
+ * Retrograde analysis. Every legal position is set up once to count its moves
+ * and to look up where its captures and promotions lead in the smaller tables.
+ * Starting from the checkmates, positions are then resolved in the order of
+ * their distance to mate by taking back moves: the positions before a loss are
+ * won, and a position is lost once every one of its moves leads to a win for
+ * the opponent.
+ */
+pub(super) fn generate(material: &Material, tablebase: &Tablebase) -> Table {
+    // Positions that are legal but not resolved yet
+    const UNKNOWN: u8 = 254;
+
+    let pieces = material.pieces();
+    let size = 2 << (6 * pieces.len());
+    let mut values = vec![INVALID; size];
+    // Moves that stay in the table and don't lead to a win for the opponent yet
+    let mut moves_left = vec![0u8; size];
+    // The best capture or promotion, INVALID if there is none
+    let mut exits = vec![INVALID; size];
+    // The positions that may be won or lost after each number of half moves, wins are odd
+    let mut plies: Vec<Vec<u32>> = vec![Vec::new(); MAX_PLY as usize + 3];
+
+    for index in 0..size {
+        let (squares, turn) = decode(index, pieces.len());
+        let squares = &squares[..pieces.len()];
+        if !is_canonical(&pieces, squares) {
+            continue;
+        }
+        let board = match build(&pieces, squares, turn) {
+            Ok(board) => board,
+            Err(_) => continue,
+        };
+        values[index] = UNKNOWN;
+
+        let moves = board.generate_legal_moves();
+        if moves.is_empty() {
+            match board.is_in_check() {
+                true => plies[0].push(index as u32),
+                false => values[index] = DRAW,
+            }
+            continue;
+        }
+        for mv in moves.iter() {
+            if board.piece_at(mv.to).is_none() && mv.promotion.is_none() {
+                moves_left[index] += 1;
+                continue;
+            }
+            let mut after = board;
+            after.make_move(mv).unwrap();
+            let value = back(
+                tablebase
+                    .value(&after)
+                    .expect("tables for captures and promotions are generated first"),
+            );
+            if exits[index] == INVALID || score(value) > score(exits[index]) {
+                exits[index] = value;
+            }
+        }
+        match exits[index] {
+            DRAW | INVALID => {}
+            exit if exit < LOSS => plies[exit as usize].push(index as u32),
+            // Every move is a capture or promotion and all of them lose
+            exit if moves_left[index] == 0 => plies[(exit - LOSS) as usize].push(index as u32),
+            _ => {}
+        }
+    }
+
+    let mut before = Vec::new();
+    for ply in 0..plies.len() {
+        let resolved = std::mem::take(&mut plies[ply]);
+        let win = ply % 2 == 1;
+        for &index in resolved.iter() {
+            let index = index as usize;
+            if values[index] != UNKNOWN {
+                continue;
+            }
+            values[index] = match win {
+                true => ply as u8,
+                false => LOSS + ply as u8,
+            };
+            predecessors(&pieces, index, &mut before);
+            for &previous in before.iter() {
+                if values[previous] != UNKNOWN {
+                    continue;
+                }
+                if !win {
+                    plies[ply + 1].push(previous as u32);
+                    continue;
+                }
+                moves_left[previous] -= 1;
+                if moves_left[previous] > 0 {
+                    continue;
+                }
+                match exits[previous] {
+                    INVALID => plies[ply + 1].push(previous as u32),
+                    // Held out longer with a capture or promotion
+                    exit if exit >= LOSS => {
+                        plies[(ply + 1).max((exit - LOSS) as usize)].push(previous as u32)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    for value in values.iter_mut() {
+        if *value == UNKNOWN {
+            *value = DRAW;
+        }
+    }
+    Table {
+        material: material.clone(),
+        values,
+    }
+}